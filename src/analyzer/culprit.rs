@@ -0,0 +1,506 @@
+use super::static_analyzer::PluginNameNormalizer;
+use crate::parser::{
+    log_level::EntryLevel,
+    parser::LogEntry,
+    stacktrace::{StackFrame, StackTrace},
+    thread_dump::ThreadDump,
+};
+use regex::Regex;
+use serde::Serialize;
+use std::{cmp::Reverse, collections::HashMap, sync::LazyLock};
+
+/// Evidence kept per suspect, the score still counts all of it
+const MAX_EVIDENCE: usize = 5;
+
+// Packages of the server, JDK and bundled libraries, never the culprit themselves
+const PLATFORM_PACKAGES: &[&str] = &[
+    "java.",
+    "javax.",
+    "jdk.",
+    "sun.",
+    "com.sun.",
+    "net.minecraft.",
+    "com.mojang.",
+    "org.bukkit.",
+    "org.spigotmc.",
+    "io.papermc.",
+    "com.destroystokyo.paper.",
+    "ca.spottedleaf.",
+    "net.md_5.",
+    "com.velocitypowered.",
+    "net.minecraftforge.",
+    "net.neoforged.",
+    "cpw.mods.",
+    "net.fabricmc.",
+    "org.spongepowered.asm.",
+    "io.netty.",
+    "com.google.",
+    "org.apache.",
+    "org.slf4j.",
+    "it.unimi.dsi.",
+    "org.yaml.snakeyaml.",
+    "kotlin.",
+];
+
+// Modules of the JVM and the server in Forge frames, `TRANSFORMER/minecraft@1.20.1/`
+const PLATFORM_MODULES: &[&str] = &["minecraft", "forge", "neoforge", "fml", "mixin"];
+
+// Error occurred while enabling Foo v1.0 (Is it up to date?)
+static ENABLE_ERROR_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"Error occurred while (?:enabling|disabling|loading) (\S+)")
+        .unwrap_or_else(|e| panic!("Failed to create 'ENABLE_ERROR_REGEX': {}", e))
+});
+
+// Could not pass event PlayerJoinEvent to Foo v1.0, Task #12 for Foo v1.0 generated an exception,
+// Could not load 'plugins/Foo.jar' in folder 'plugins'
+static PLUGIN_ERROR_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?:Could not pass event \S+ to (\S+)|Task #\d+ for (\S+) .*generated an exception|Could not load '(?:[^']*/)?([^'/]+?)(?:-[\d.]+[^'/]*)?\.jar')",
+    )
+    .unwrap_or_else(|e| panic!("Failed to create 'PLUGIN_ERROR_REGEX': {}", e))
+});
+
+#[derive(Serialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub enum SuspectKind {
+    Plugin,
+    Mod,
+    /// Code that couldn't be matched to a loaded plugin or mod
+    Package,
+}
+
+#[derive(Serialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub enum EvidenceKind {
+    /// `Error occurred while enabling X`
+    EnableError,
+    /// The code the `Server thread` was stuck in when the watchdog dumped it
+    Hang,
+    /// `Could not pass event ... to X` and similar messages naming the plugin
+    PluginError,
+    /// The first frame of the root cause outside the server and JDK
+    TopFrame,
+    /// Any other frame in a stack trace
+    Frame,
+    /// A warning or error logged with the plugin's `[Name]` prefix
+    Logged,
+}
+
+impl EvidenceKind {
+    fn weight(&self) -> u32 {
+        match self {
+            EvidenceKind::EnableError => 10,
+            EvidenceKind::Hang => 10,
+            EvidenceKind::PluginError => 8,
+            EvidenceKind::TopFrame => 5,
+            EvidenceKind::Logged => 3,
+            EvidenceKind::Frame => 2,
+        }
+    }
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Evidence {
+    pub kind: EvidenceKind,
+    /// 1-based line number
+    pub line: usize,
+    pub text: String,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Suspect {
+    pub name: String,
+    pub kind: SuspectKind,
+    pub score: u32,
+    pub evidence: Vec<Evidence>,
+}
+
+/// Ranks the plugins and mods most likely responsible for the log's errors and hangs.
+///
+/// `plugins` are the loaded plugins (name to version), used to map packages and jars in
+/// stack frames back to plugin names.
+pub fn suspects(
+    entries: &[LogEntry],
+    thread_dumps: &[ThreadDump],
+    plugins: &HashMap<String, String>,
+) -> Vec<Suspect> {
+    let mut culprits = Culprits::new(plugins);
+
+    for entry in entries {
+        culprits.entry(entry);
+    }
+
+    for dump in thread_dumps {
+        culprits.thread_dump(dump);
+    }
+
+    culprits.into_suspects()
+}
+
+/// The plugin or mod at the top of the `Server thread` stack of a watchdog thread dump, what
+/// the server was stuck on.
+pub fn hang_suspect(dump: &ThreadDump, plugins: &HashMap<String, String>) -> Option<Suspect> {
+    let mut culprits = Culprits::new(plugins);
+    culprits.thread_dump(dump);
+    culprits.suspects.pop()
+}
+
+struct Culprits<'a> {
+    /// Normalized plugin name to plugin name
+    plugins: HashMap<String, &'a str>,
+    suspects: Vec<Suspect>,
+}
+
+impl<'a> Culprits<'a> {
+    fn new(plugins: &'a HashMap<String, String>) -> Self {
+        let plugins = plugins
+            .keys()
+            .map(|name| (PluginNameNormalizer(name).clear(), name.as_str()))
+            .collect();
+
+        Self {
+            plugins,
+            suspects: Vec::new(),
+        }
+    }
+
+    fn entry(&mut self, entry: &LogEntry) {
+        let first_line = entry
+            .to_string()
+            .lines()
+            .next()
+            .unwrap_or_default()
+            .to_string();
+
+        if let Some(captures) = ENABLE_ERROR_REGEX.captures(&first_line) {
+            let (name, kind) = self.plugin_or_name(&captures[1]);
+            self.add(
+                name,
+                kind,
+                EvidenceKind::EnableError,
+                entry.start_line,
+                &first_line,
+            );
+        }
+
+        if let Some(name) = PLUGIN_ERROR_REGEX
+            .captures(&first_line)
+            .and_then(|captures| captures.iter().skip(1).flatten().next())
+        {
+            let (name, kind) = self.plugin_or_name(name.as_str());
+            self.add(
+                name,
+                kind,
+                EvidenceKind::PluginError,
+                entry.start_line,
+                &first_line,
+            );
+        }
+
+        let is_problem = matches!(
+            entry.severity,
+            EntryLevel::Warn | EntryLevel::Error | EntryLevel::Fatal
+        );
+
+        if let Some(name) = entry.logger.as_deref().filter(|_| is_problem) {
+            if let Some(plugin) = self.plugin(name) {
+                let plugin = plugin.to_string();
+                self.add(
+                    plugin,
+                    SuspectKind::Plugin,
+                    EvidenceKind::Logged,
+                    entry.start_line,
+                    &first_line,
+                );
+            }
+        }
+
+        if let Some(trace) = &entry.stack_trace {
+            self.stack_trace(entry, trace);
+        }
+    }
+
+    fn stack_trace(&mut self, entry: &LogEntry, trace: &StackTrace) {
+        let top_frame = trace
+            .root_cause()
+            .frames
+            .iter()
+            .find_map(|frame| self.frame_suspect(frame).map(|suspect| (suspect, frame)));
+
+        // Each suspect counts once per entry, however many of its frames the trace has
+        let mut seen: Vec<(String, SuspectKind)> = Vec::new();
+
+        if let Some((suspect, frame)) = top_frame {
+            let (line, text) = frame_line(entry, frame);
+            self.add(
+                suspect.0.clone(),
+                suspect.1,
+                EvidenceKind::TopFrame,
+                line,
+                &text,
+            );
+            seen.push(suspect);
+        }
+
+        for trace in trace.all() {
+            for frame in &trace.frames {
+                let Some(suspect) = self.frame_suspect(frame) else {
+                    continue;
+                };
+
+                if seen.contains(&suspect) {
+                    continue;
+                }
+
+                let (line, text) = frame_line(entry, frame);
+                self.add(
+                    suspect.0.clone(),
+                    suspect.1,
+                    EvidenceKind::Frame,
+                    line,
+                    &text,
+                );
+                seen.push(suspect);
+            }
+        }
+    }
+
+    fn thread_dump(&mut self, dump: &ThreadDump) {
+        let Some(thread) = dump.server_thread() else {
+            return;
+        };
+
+        let suspects: Vec<_> = thread
+            .frames
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, frame)| Some((idx, frame, self.frame_suspect(frame)?)))
+            .collect();
+
+        // Drivers and libraries shaded into a plugin sit above the plugin's own frames, e.g.
+        // a MySQL read, so a plugin or mod further down is preferred
+        let top = suspects
+            .iter()
+            .find(|(.., (_, kind))| *kind != SuspectKind::Package)
+            .or(suspects.first());
+
+        if let Some((idx, frame, (name, kind))) = top {
+            self.add(
+                name.clone(),
+                *kind,
+                EvidenceKind::Hang,
+                thread.stack_line + idx,
+                &frame.to_string(),
+            );
+        }
+    }
+
+    /// Plugin, mod or package the frame's code belongs to.
+    fn frame_suspect(&self, frame: &StackFrame) -> Option<(String, SuspectKind)> {
+        if PLATFORM_PACKAGES
+            .iter()
+            .any(|package| frame.class.starts_with(package))
+        {
+            return None;
+        }
+
+        let package: Vec<&str> = frame.class.split('.').collect();
+        let package = &package[..package.len().saturating_sub(1)];
+
+        let by_jar = frame
+            .jar
+            .as_deref()
+            .and_then(|jar| jar.split(':').next())
+            .and_then(|jar| self.plugin_by_jar(jar));
+
+        // Only the `tld.vendor.name` head of the package, deeper segments are generic names
+        // like `api`, `core` or `commands` that may collide with a plugin's name
+        let by_package = || {
+            [2, 1]
+                .iter()
+                .filter_map(|idx| package.get(*idx))
+                .find_map(|segment| self.plugin(segment))
+        };
+
+        if let Some(plugin) = by_jar.or_else(by_package) {
+            return Some((plugin.to_string(), SuspectKind::Plugin));
+        }
+
+        let module = frame
+            .module
+            .as_deref()
+            .map(|module| module.split('@').next().unwrap_or(module))
+            .filter(|module| !module.contains('.') && !PLATFORM_MODULES.contains(module));
+
+        if let Some(module) = module {
+            return Some((module.to_string(), SuspectKind::Mod));
+        }
+
+        // Classes in the default package can't be told apart from anything else
+        if package.is_empty() {
+            return None;
+        }
+
+        let package = package[..package.len().min(3)].join(".");
+        Some((package, SuspectKind::Package))
+    }
+
+    fn plugin(&self, name: &str) -> Option<&'a str> {
+        self.plugins
+            .get(&PluginNameNormalizer(name).clear())
+            .copied()
+    }
+
+    /// Plugin the jar belongs to, ignoring its version and platform suffix:
+    /// `LuckPerms-Bukkit-5.4.102.jar` is LuckPerms, but `EssentialsChat.jar` isn't Essentials.
+    fn plugin_by_jar(&self, jar: &str) -> Option<&'a str> {
+        let parts: Vec<&str> = jar
+            .trim_end_matches(".jar")
+            .split(['-', '_', ' '])
+            .take_while(|part| !is_version(part))
+            .collect();
+
+        (1..=parts.len())
+            .rev()
+            .find_map(|len| self.plugin(&parts[..len].concat()))
+    }
+
+    fn plugin_or_name(&self, name: &str) -> (String, SuspectKind) {
+        match self.plugin(name) {
+            Some(plugin) => (plugin.to_string(), SuspectKind::Plugin),
+            // Failed plugins may never have logged `Loading X`
+            None => (name.to_string(), SuspectKind::Plugin),
+        }
+    }
+
+    fn add(
+        &mut self,
+        name: String,
+        kind: SuspectKind,
+        evidence: EvidenceKind,
+        line: usize,
+        text: &str,
+    ) {
+        let idx = match self
+            .suspects
+            .iter()
+            .position(|suspect| suspect.name == name && suspect.kind == kind)
+        {
+            Some(idx) => idx,
+            None => {
+                self.suspects.push(Suspect {
+                    name,
+                    kind,
+                    score: 0,
+                    evidence: Vec::new(),
+                });
+                self.suspects.len() - 1
+            }
+        };
+
+        let suspect = &mut self.suspects[idx];
+        suspect.score += evidence.weight();
+
+        if suspect.evidence.len() < MAX_EVIDENCE {
+            suspect.evidence.push(Evidence {
+                kind: evidence,
+                line,
+                text: text.trim().to_string(),
+            });
+        }
+    }
+
+    fn into_suspects(mut self) -> Vec<Suspect> {
+        // Stable, ties stay in order of appearance
+        self.suspects.sort_by_key(|suspect| Reverse(suspect.score));
+        self.suspects
+    }
+}
+
+/// `5.4.102`, `v2.0`, `1.20.4-R0.1-SNAPSHOT`
+fn is_version(part: &str) -> bool {
+    part.trim_start_matches(['v', 'V'])
+        .starts_with(|c: char| c.is_ascii_digit())
+}
+
+/// Line number and text of the frame's `at` line in the entry.
+fn frame_line(entry: &LogEntry, frame: &StackFrame) -> (usize, String) {
+    let needle = format!("{}.{}(", frame.class, frame.method);
+
+    entry
+        .to_string()
+        .lines()
+        .enumerate()
+        .find(|(_, line)| line.contains(&needle))
+        .map(|(idx, line)| (entry.start_line + idx, line.to_string()))
+        .unwrap_or_else(|| {
+            (
+                entry.start_line,
+                format!("at {}.{}", frame.class, frame.method),
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn suspect(plugins: &[&str], frame: &str) -> Option<(String, SuspectKind)> {
+        let plugins = plugins
+            .iter()
+            .map(|name| (name.to_string(), "1.0".to_string()))
+            .collect();
+
+        Culprits::new(&plugins).frame_suspect(&StackFrame::parse(frame).unwrap())
+    }
+
+    #[test]
+    fn plugin_by_package() {
+        assert_eq!(
+            suspect(
+                &["LuckPerms"],
+                "at me.lucko.luckperms.common.api.LuckPermsApiProvider.getUserManager(LuckPermsApiProvider.java:150)"
+            ),
+            Some(("LuckPerms".to_string(), SuspectKind::Plugin))
+        );
+
+        // `Core` and `Commands` are deep in another plugin's package
+        assert_eq!(
+            suspect(
+                &["Core", "Commands"],
+                "at com.example.shop.core.commands.BuyCommand.execute(BuyCommand.java:42)"
+            ),
+            Some(("com.example.shop".to_string(), SuspectKind::Package))
+        );
+    }
+
+    #[test]
+    fn plugin_by_jar() {
+        assert_eq!(
+            suspect(
+                &["Essentials", "EssentialsChat"],
+                "at com.earth2me.essentials.chat.EssentialsChatPlayerListener.onPlayerChat(EssentialsChatPlayerListener.java:60) ~[EssentialsChat-2.20.1.jar:?]"
+            ),
+            Some(("EssentialsChat".to_string(), SuspectKind::Plugin))
+        );
+
+        assert_eq!(
+            suspect(
+                &["LuckPerms"],
+                "at me.lucko.luckperms.lib.Foo.bar(Foo.java:1) ~[LuckPerms-Bukkit-5.4.102.jar:?]"
+            ),
+            Some(("LuckPerms".to_string(), SuspectKind::Plugin))
+        );
+
+        // The jar isn't the loaded plugin's, only a name starting like it
+        assert_eq!(
+            suspect(
+                &["Essentials"],
+                "at net.example.chat.ChatListener.onChat(ChatListener.java:12) ~[EssentialsChatAddon-1.0.jar:?]"
+            ),
+            Some(("net.example.chat".to_string(), SuspectKind::Package))
+        );
+    }
+}
@@ -0,0 +1,70 @@
+use rhai::ImmutableString;
+
+use crate::analyzer::DynamicAnalyzerDetails;
+use crate::parser::crash_report::CrashReport;
+
+/// The crash report for scripts, values are empty when there's no report.
+#[derive(Clone)]
+pub struct Crash {
+    dad: DynamicAnalyzerDetails,
+}
+
+impl Crash {
+    pub fn new(dad: DynamicAnalyzerDetails) -> Self {
+        Self { dad }
+    }
+
+    fn report(&self) -> Option<&CrashReport> {
+        self.dad.crash_report.as_ref()
+    }
+
+    pub fn exists(self) -> bool {
+        self.report().is_some()
+    }
+
+    pub fn description(&mut self) -> String {
+        self.report()
+            .and_then(|report| report.description.clone())
+            .unwrap_or_default()
+    }
+
+    /// Class of the crash's exception, e.g. `java.lang.NullPointerException`.
+    pub fn exception(&mut self) -> String {
+        self.report()
+            .and_then(|report| report.exception.as_ref())
+            .map(|exception| exception.class.clone())
+            .unwrap_or_default()
+    }
+
+    /// A value from `System Details`, e.g. `detail("Java Version")`.
+    pub fn detail(self, key: ImmutableString) -> String {
+        self.report()
+            .and_then(|report| report.system_detail(&key))
+            .map(String::from)
+            .unwrap_or_default()
+    }
+
+    /// A value from another section, e.g. `section_detail("Head", "Thread")`.
+    pub fn section_detail(self, title: ImmutableString, key: ImmutableString) -> String {
+        self.report()
+            .and_then(|report| report.section(&title))
+            .and_then(|section| section.detail(&key))
+            .map(String::from)
+            .unwrap_or_default()
+    }
+
+    /// Whether the report has a `-- title --` section, e.g. `Block entity being ticked`.
+    pub fn has_section(self, title: ImmutableString) -> bool {
+        self.report()
+            .is_some_and(|report| report.section(&title).is_some())
+    }
+
+    pub fn is_suspected(self, id: ImmutableString) -> bool {
+        self.report().is_some_and(|report| {
+            report
+                .suspected_mods
+                .iter()
+                .any(|suspected| suspected.id == id.as_str())
+        })
+    }
+}
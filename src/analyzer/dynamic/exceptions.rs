@@ -0,0 +1,59 @@
+use rhai::ImmutableString;
+
+use crate::analyzer::DynamicAnalyzerDetails;
+use crate::parser::stacktrace::StackTrace;
+
+/// Stack traces for scripts, classes can be given with or without their package.
+#[derive(Clone)]
+pub struct Exceptions {
+    dad: DynamicAnalyzerDetails,
+}
+
+impl Exceptions {
+    pub fn new(dad: DynamicAnalyzerDetails) -> Self {
+        Self { dad }
+    }
+
+    fn all(&self) -> impl Iterator<Item = &StackTrace> {
+        self.dad.stack_traces.iter().flat_map(|trace| trace.all())
+    }
+
+    /// Whether the exception was thrown anywhere, including as a cause.
+    pub fn has(self, class: ImmutableString) -> bool {
+        self.all().any(|trace| trace.is(&class))
+    }
+
+    pub fn has_root_cause(self, class: ImmutableString) -> bool {
+        self.dad
+            .stack_traces
+            .iter()
+            .any(|trace| trace.root_cause().is(&class))
+    }
+
+    /// Whether any frame is in a class starting with `prefix`, e.g. a plugin's package.
+    pub fn has_frame(self, prefix: ImmutableString) -> bool {
+        self.all()
+            .flat_map(|trace| &trace.frames)
+            .any(|frame| frame.class.starts_with(prefix.as_str()))
+    }
+
+    /// Number of stack traces the exception appears in.
+    pub fn count(self, class: ImmutableString) -> i32 {
+        let count = self
+            .dad
+            .stack_traces
+            .iter()
+            .filter(|trace| trace.all().iter().any(|trace| trace.is(&class)))
+            .count();
+
+        i32::try_from(count).unwrap_or(i32::MAX)
+    }
+
+    /// Message of the first such exception, empty when there's none.
+    pub fn message(self, class: ImmutableString) -> String {
+        self.all()
+            .find(|trace| trace.is(&class))
+            .and_then(|trace| trace.message.clone())
+            .unwrap_or_default()
+    }
+}
@@ -0,0 +1,65 @@
+use rhai::ImmutableString;
+
+use crate::analyzer::DynamicAnalyzerDetails;
+use crate::parser::fatal_error::FatalErrorLog;
+
+/// The JVM fatal error log for scripts, values are empty when the log isn't one.
+#[derive(Clone)]
+pub struct FatalError {
+    dad: DynamicAnalyzerDetails,
+}
+
+impl FatalError {
+    pub fn new(dad: DynamicAnalyzerDetails) -> Self {
+        Self { dad }
+    }
+
+    fn log(&self) -> Option<&FatalErrorLog> {
+        self.dad.fatal_error.as_ref()
+    }
+
+    pub fn exists(self) -> bool {
+        self.log().is_some()
+    }
+
+    /// e.g. `out_of_native_memory`, `container_out_of_memory`, `gpu_driver` or
+    /// `native_library`.
+    pub fn cause(&mut self) -> String {
+        self.log()
+            .and_then(|log| log.cause)
+            .map(|cause| cause.name().to_string())
+            .unwrap_or_default()
+    }
+
+    /// e.g. `SIGSEGV (0xb)`.
+    pub fn signal(&mut self) -> String {
+        self.log()
+            .and_then(|log| log.signal.clone())
+            .unwrap_or_default()
+    }
+
+    /// Library of the problematic frame, e.g. `nvoglv64.dll`.
+    pub fn library(&mut self) -> String {
+        self.log()
+            .and_then(|log| log.problematic_frame.as_ref())
+            .and_then(|frame| frame.library.clone())
+            .unwrap_or_default()
+    }
+
+    /// Whether a frame of the native or Java stack contains `symbol`, e.g. `org.lwjgl`.
+    pub fn has_frame(self, symbol: ImmutableString) -> bool {
+        self.log().is_some_and(|log| {
+            log.problematic_frame
+                .iter()
+                .chain(&log.native_frames)
+                .chain(&log.java_frames)
+                .any(|frame| {
+                    frame.symbol.contains(symbol.as_str())
+                        || frame
+                            .library
+                            .as_deref()
+                            .is_some_and(|library| library.contains(symbol.as_str()))
+                })
+        })
+    }
+}
@@ -1,4 +1,5 @@
 use crate::analyzer::dynamic::chunks::Chunks;
+use crate::{Error, Result};
 use regex::Regex;
 use rhai::{ImmutableString, AST};
 use semver::{Version, VersionReq};
@@ -8,12 +9,20 @@ use std::{
     collections::HashMap,
     path::{Path, PathBuf},
 };
-use {plugins::Plugins, ports::Ports, server::Server};
+use {
+    crash::Crash, exceptions::Exceptions, fatal_error::FatalError, mods::Mods, plugins::Plugins,
+    ports::Ports, server::Server, watchdog::Watchdog,
+};
 
 pub mod chunks;
+pub mod crash;
+pub mod exceptions;
+pub mod fatal_error;
+pub mod mods;
 pub mod plugins;
 pub mod ports;
 pub mod server;
+pub mod watchdog;
 
 pub static SCRIPTS_DIRECTORY: LazyLock<PathBuf> = LazyLock::new(|| {
     let current_directory = std::env::current_dir().unwrap();
@@ -70,23 +79,16 @@ impl ScriptPlatform {
         .into_iter()
     }
 
-    pub fn script_paths(&self) -> Vec<PathBuf> {
+    pub fn script_paths(&self) -> Result<Vec<PathBuf>> {
         let mut files = vec![];
 
-        for file in std::fs::read_dir(self.directory()).unwrap().flatten() {
-            if file
-                .path()
-                .file_name()
-                .unwrap()
-                .to_str()
-                .unwrap()
-                .starts_with('_')
-            {
+        for file in std::fs::read_dir(self.directory())?.flatten() {
+            if file.file_name().to_string_lossy().starts_with('_') {
                 continue;
             }
             files.push(file.path());
         }
-        files
+        Ok(files)
     }
 }
 
@@ -113,23 +115,35 @@ pub struct DynamicAnalyzer {
 }
 
 impl DynamicAnalyzer {
-    pub fn scripts(&self, script_platform: ScriptPlatform) -> Vec<Script> {
+    pub fn scripts(&self, script_platform: ScriptPlatform) -> Result<Vec<Script>> {
         let mut scripts = Vec::new();
 
-        for file in script_platform.script_paths() {
-            let file_name = file.file_name().unwrap().to_str().unwrap().to_string();
-            let script_file_content = std::fs::read_to_string(&file).unwrap();
-            let script_split = script_file_content.split_once("///").unwrap();
+        for file in script_platform.script_paths()? {
+            let file_name = file
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let script_file_content = std::fs::read_to_string(&file)?;
+            let Some(script_split) = script_file_content.split_once("///") else {
+                return Err(Error::MissingDetections { path: file });
+            };
 
             let script_content = script_split.0.to_string();
             let toml = script_split.1.to_string();
 
+            // One script with broken detections shouldn't disable all the others
             let detection: HashMap<String, Detection> = match toml::from_str(&toml) {
                 Ok(hm) => hm,
-                Err(_) => continue,
+                Err(e) => {
+                    log::warn!("Skipping script '{}': {}", file.display(), e);
+                    continue;
+                }
             };
 
-            let ast = self.ast(&script_content);
+            let ast = match self.ast(&script_content) {
+                Ok(ast) => ast,
+                Err(source) => return Err(Error::ScriptCompile { path: file, source }),
+            };
 
             scripts.push(Script {
                 file: file_name,
@@ -140,23 +154,28 @@ impl DynamicAnalyzer {
             })
         }
 
-        scripts
+        Ok(scripts)
     }
 
-    fn ast(&self, content: &String) -> AST {
+    fn ast(&self, content: &String) -> std::result::Result<AST, rhai::ParseError> {
         let content = format!(
             "
             let ports = new_ports(dad);
             let plugins = new_plugins(dad);
             let server = new_server(dad);
             let chunks = new_chunks(dad);
+            let exceptions = new_exceptions(dad);
+            let mods = new_mods(dad);
+            let crash = new_crash(dad);
+            let fatal_error = new_fatal_error(dad);
+            let watchdog = new_watchdog(dad);
             {}
             return ();
             ",
             content
         );
 
-        self.engine.compile(content).unwrap()
+        self.engine.compile(content)
     }
 }
 
@@ -193,6 +212,7 @@ impl Default for DynamicAnalyzer {
             .register_fn("is_known_version", Server::is_known_version)
             .register_fn("is_bukkit_based", Server::is_bukkit_based)
             .register_get("version", Server::version)
+            .register_get("java_version", Server::java_version)
             .register_get("platform", Server::platform);
 
         engine
@@ -202,6 +222,49 @@ impl Default for DynamicAnalyzer {
             .register_fn("has_line", Chunks::has_line2)
             .register_fn("has_line_permissive", Chunks::has_line_permissive);
 
+        engine
+            .register_type::<Exceptions>()
+            .register_fn("new_exceptions", Exceptions::new)
+            .register_fn("has", Exceptions::has)
+            .register_fn("has_root_cause", Exceptions::has_root_cause)
+            .register_fn("has_frame", Exceptions::has_frame)
+            .register_fn("count", Exceptions::count)
+            .register_fn("message", Exceptions::message);
+
+        engine
+            .register_type::<Mods>()
+            .register_fn("new_mods", Mods::new)
+            .register_fn("has", Mods::has)
+            .register_fn("version", Mods::version);
+
+        engine
+            .register_type::<Crash>()
+            .register_fn("new_crash", Crash::new)
+            .register_fn("exists", Crash::exists)
+            .register_fn("detail", Crash::detail)
+            .register_fn("section_detail", Crash::section_detail)
+            .register_fn("has_section", Crash::has_section)
+            .register_fn("is_suspected", Crash::is_suspected)
+            .register_get("description", Crash::description)
+            .register_get("exception", Crash::exception);
+
+        engine
+            .register_type::<FatalError>()
+            .register_fn("new_fatal_error", FatalError::new)
+            .register_fn("exists", FatalError::exists)
+            .register_fn("has_frame", FatalError::has_frame)
+            .register_get("cause", FatalError::cause)
+            .register_get("signal", FatalError::signal)
+            .register_get("library", FatalError::library);
+
+        engine
+            .register_type::<Watchdog>()
+            .register_fn("new_watchdog", Watchdog::new)
+            .register_fn("exists", Watchdog::exists)
+            .register_fn("has_frame", Watchdog::has_frame)
+            .register_get("culprit", Watchdog::culprit)
+            .register_get("state", Watchdog::state);
+
         engine
             .register_type::<Ports>()
             .register_fn("new_ports", Ports::new)
@@ -0,0 +1,23 @@
+use rhai::ImmutableString;
+
+use crate::analyzer::DynamicAnalyzerDetails;
+
+/// Mods from the crash report's mod list, by id.
+#[derive(Clone)]
+pub struct Mods {
+    dad: DynamicAnalyzerDetails,
+}
+
+impl Mods {
+    pub fn new(dad: DynamicAnalyzerDetails) -> Self {
+        Self { dad }
+    }
+
+    pub fn has(self, id: ImmutableString) -> bool {
+        self.dad.mods.contains_key(id.as_str())
+    }
+
+    pub fn version(self, id: ImmutableString) -> Option<String> {
+        self.dad.mods.get(id.as_str()).cloned()
+    }
+}
@@ -33,6 +33,13 @@ impl Server {
         }
     }
 
+    pub fn java_version(&mut self) -> String {
+        match &self.dad.java_version {
+            None => "Unknown".to_string(),
+            Some(ver) => ver.to_owned(),
+        }
+    }
+
     pub fn platform(&mut self) -> &'static str {
         self.dad.platform.name()
     }
@@ -0,0 +1,54 @@
+use rhai::ImmutableString;
+
+use crate::analyzer::DynamicAnalyzerDetails;
+use crate::parser::thread_dump::DumpedThread;
+
+/// Watchdog thread dumps for scripts, about the last dump of the log.
+#[derive(Clone)]
+pub struct Watchdog {
+    dad: DynamicAnalyzerDetails,
+}
+
+impl Watchdog {
+    pub fn new(dad: DynamicAnalyzerDetails) -> Self {
+        Self { dad }
+    }
+
+    fn server_thread(&self) -> Option<&DumpedThread> {
+        self.dad
+            .thread_dumps
+            .last()
+            .and_then(|dump| dump.server_thread())
+    }
+
+    /// Whether the server stopped responding, early warnings included.
+    pub fn exists(self) -> bool {
+        !self.dad.thread_dumps.is_empty()
+    }
+
+    /// Plugin or mod the server was stuck on, empty when it's unknown.
+    pub fn culprit(&mut self) -> String {
+        self.dad
+            .hang
+            .as_ref()
+            .map(|hang| hang.name.clone())
+            .unwrap_or_default()
+    }
+
+    /// State of the `Server thread`, e.g. `RUNNABLE` or `BLOCKED`.
+    pub fn state(&mut self) -> String {
+        self.server_thread()
+            .and_then(|thread| thread.state.clone())
+            .unwrap_or_default()
+    }
+
+    /// Whether a frame of the `Server thread` starts with `class`, e.g. `com.mysql`.
+    pub fn has_frame(self, class: ImmutableString) -> bool {
+        self.server_thread().is_some_and(|thread| {
+            thread
+                .frames
+                .iter()
+                .any(|frame| frame.class.starts_with(class.as_str()))
+        })
+    }
+}
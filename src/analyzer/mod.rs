@@ -1,10 +1,24 @@
-use crate::parser::parser::Parser;
+use crate::log::set::SourcedLine;
+use crate::parser::{
+    crash_report::CrashReport,
+    fatal_error::FatalErrorLog,
+    layout::Layout,
+    log_level::CustomLevel,
+    parser::{apply_custom_levels, LogEntry, Parser},
+    stacktrace::StackTrace,
+    thread_dump::ThreadDump,
+};
+use crate::{Error, Result};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::{collections::HashMap, ops::Range, path::Path, sync::Arc};
 
+use self::culprit::Suspect;
+use self::session::Session;
 use self::static_analyzer::StaticAnalyzer;
 
+pub mod culprit;
 pub mod dynamic;
+pub mod session;
 pub mod static_analyzer;
 pub mod template;
 
@@ -80,15 +94,171 @@ pub struct VanillaPorts {
 pub struct Analyzer {
     pub lines: Vec<String>,
     pub platform: Platform,
+    /// A crash report file, or one printed into the log
+    pub crash_report: Option<CrashReport>,
+    /// When the lines are a JVM fatal error log, `hs_err_pid*.log`
+    pub fatal_error: Option<FatalErrorLog>,
+    /// Line ranges of the server's sessions in `lines`, see [`session::session_ranges`]
+    pub session_ranges: Vec<Range<usize>>,
+    /// Files the lines were read from, oldest first, see [`Analyzer::from_sourced_lines`]
+    pub sources: Vec<SourceFile>,
+    structured: Option<Structured>,
+}
+
+/// A file of a [`LogSet`](crate::log::set::LogSet) timeline.
+#[derive(Clone, Debug)]
+pub struct SourceFile {
+    pub path: Arc<Path>,
+    /// 0-based range of the file's lines in the timeline
+    pub lines: Range<usize>,
+}
+
+/// A log written with a structured layout, analyzed through its text rendering.
+#[derive(Clone, Debug)]
+struct Structured {
+    /// Parsed without custom levels, see [`apply_custom_levels`]
+    entries: Vec<LogEntry>,
+    /// Index of the entry each rendered line belongs to
+    line_entries: Vec<usize>,
+}
+
+impl Structured {
+    /// See [`Analyzer::source_lines`].
+    fn source_lines(&self, range: &Range<usize>) -> (usize, usize) {
+        if range.is_empty() {
+            return (range.start + 1, range.end);
+        }
+
+        let first = &self.entries[self.line_entries[range.start]];
+        let last = &self.entries[self.line_entries[range.end - 1]];
+
+        (first.start_line, last.end_line)
+    }
+
+    /// Index of the first rendered line at or after the 0-based line `idx` of the log.
+    fn rendered_line(&self, idx: usize) -> usize {
+        self.line_entries
+            .partition_point(|entry| self.entries[*entry].start_line <= idx)
+    }
 }
 
 impl Analyzer {
-    pub fn new(lines: &[String]) -> Self {
-        let platform = determine_platform(lines);
+    /// Logs written with a structured layout are analyzed as text, rendered like
+    /// [`Parser::text`]. Line numbers in the results still refer to `lines`.
+    pub fn new(lines: &[String]) -> Result<Self> {
+        Self::analyze(lines.to_vec(), Vec::new())
+    }
 
-        Self {
-            lines: lines.to_vec(),
+    /// Analyzes a [`LogSet`](crate::log::set::LogSet) timeline, spanning several restarts.
+    ///
+    /// A rotated file usually starts with a restart, so the first startup in a file starts a
+    /// new session at the file's first line, even when no shutdown was logged before it.
+    pub fn from_sourced_lines(lines: &[SourcedLine]) -> Result<Self> {
+        let mut sources: Vec<SourceFile> = Vec::new();
+
+        for (idx, line) in lines.iter().enumerate() {
+            match sources.last_mut() {
+                Some(source) if source.path == line.source => source.lines.end = idx + 1,
+                _ => sources.push(SourceFile {
+                    path: line.source.clone(),
+                    lines: idx..idx + 1,
+                }),
+            }
+        }
+
+        let lines = lines.iter().map(|line| line.line.clone()).collect();
+        Self::analyze(lines, sources)
+    }
+
+    fn analyze(lines: Vec<String>, sources: Vec<SourceFile>) -> Result<Self> {
+        let first_line = lines.first().ok_or(Error::EmptyLog)?;
+
+        let (lines, structured) = match Layout::detect(first_line) {
+            Layout::Text => (lines, None),
+            _ => {
+                let entries: Vec<LogEntry> = Parser::iter(lines, vec![]).collect();
+
+                let mut text = Vec::new();
+                let mut line_entries = Vec::new();
+
+                for (idx, entry) in entries.iter().enumerate() {
+                    for line in entry.to_string().lines() {
+                        text.push(line.to_string());
+                        line_entries.push(idx);
+                    }
+                }
+
+                if text.is_empty() {
+                    return Err(Error::EmptyLog);
+                }
+
+                (
+                    text,
+                    Some(Structured {
+                        entries,
+                        line_entries,
+                    }),
+                )
+            }
+        };
+
+        let file_starts: Vec<usize> = sources
+            .iter()
+            .map(|source| match &structured {
+                Some(structured) => structured.rendered_line(source.lines.start),
+                None => source.lines.start,
+            })
+            .collect();
+
+        let mut crash_report = CrashReport::find(&lines);
+        let fatal_error = FatalErrorLog::find(&lines);
+        let session_ranges = session::session_ranges(&lines, &file_starts);
+
+        if let (Some(report), Some(structured)) = (&mut crash_report, &structured) {
+            report.start_line = structured
+                .source_lines(&(report.start_line - 1..report.start_line))
+                .0;
+        }
+
+        // The latest session's platform, an earlier one may have run another
+        let latest_platform = session_ranges
+            .iter()
+            .map(|range| determine_platform(&lines[range.clone()]))
+            .rfind(|platform| !matches!(platform, Platform::Vanilla));
+
+        let platform = match (
+            latest_platform.unwrap_or_else(|| determine_platform(&lines)),
+            crash_report.as_ref().and_then(crash_report_platform),
+        ) {
+            // Vanilla is also what's left when nothing was found in the log
+            (Platform::Vanilla, Some(platform)) => platform,
+            (platform, _) => platform,
+        };
+
+        Ok(Self {
+            lines,
             platform,
+            crash_report,
+            fatal_error,
+            session_ranges,
+            sources,
+            structured,
+        })
+    }
+
+    /// File the 1-based `line` was read from, for [`Analyzer::from_sourced_lines`].
+    pub fn source(&self, line: usize) -> Option<&Path> {
+        self.sources
+            .iter()
+            .find(|source| source.lines.contains(&line.wrapping_sub(1)))
+            .map(|source| source.path.as_ref())
+    }
+
+    /// 1-based numbers of the first and last line in the log of the `lines` in `range`.
+    fn source_lines(&self, range: &Range<usize>) -> (usize, usize) {
+        match &self.structured {
+            Some(structured) => structured.source_lines(range),
+            None => (range.start + 1, range.end),
         }
     }
 
@@ -130,6 +300,14 @@ impl Analyzer {
         plugins
     }
 
+    /// Like `plugins`, empty for proxies as their plugins aren't read yet.
+    fn loaded_plugins(&self, line_limit: usize) -> HashMap<String, String> {
+        match self.is_proxy() {
+            true => HashMap::new(),
+            false => self.plugins(line_limit),
+        }
+    }
+
     fn version(&self) -> Option<String> {
         if self.is_proxy() {
             match self.platform {
@@ -147,10 +325,43 @@ impl Analyzer {
                     }
                 }
             }
+
+            if let Some(version) = self
+                .crash_report
+                .as_ref()
+                .and_then(|report| report.minecraft_version())
+            {
+                return Some(version.to_string());
+            }
         }
         None
     }
 
+    fn java_version(&self) -> Option<String> {
+        self.crash_report
+            .as_ref()
+            .and_then(|report| report.java_version())
+            .or_else(|| {
+                self.fatal_error
+                    .as_ref()
+                    .and_then(|fatal_error| fatal_error.java_version())
+            })
+            .map(String::from)
+    }
+
+    /// Mods from the crash report's mod list, id to version.
+    fn mods(&self) -> HashMap<String, String> {
+        let Some(crash_report) = &self.crash_report else {
+            return HashMap::new();
+        };
+
+        crash_report
+            .mods
+            .iter()
+            .map(|m| (m.id.clone(), m.version.clone().unwrap_or_default()))
+            .collect()
+    }
+
     fn vanilla_ports(&self) -> VanillaPorts {
         const SERVER_PORT_MESSAGE: &str = "Starting Minecraft server on";
         const QUERY_PORT_MESSAGE: &str = "Query running on";
@@ -221,44 +432,170 @@ impl Analyzer {
         self.ports(&ports_root.ports.mods, ports_lines_limit)
     }
 
-    pub fn build(self, plugins_limit: usize, ports_limit: usize) -> DynamicAnalyzerDetails {
-        let current_directory = std::env::current_dir().unwrap();
+    /// The analyzer of one session, on its lines only.
+    fn session_analyzer(&self, range: &Range<usize>) -> Self {
+        let lines = self.lines[range.clone()].to_vec();
+
+        // Vanilla is also what's left when the session didn't get far enough to tell, or the
+        // log starts partway through it
+        let platform = match determine_platform(&lines) {
+            Platform::Vanilla => self.platform,
+            platform => platform,
+        };
+
+        Self {
+            // Only read by `sessions`, which isn't called on a session
+            session_ranges: Vec::new(),
+            lines,
+            platform,
+            crash_report: None,
+            fatal_error: None,
+            sources: Vec::new(),
+            structured: None,
+        }
+    }
+
+    fn sessions(
+        &self,
+        ports_root: &PortsRoot,
+        plugins_limit: usize,
+        ports_limit: usize,
+    ) -> Vec<Session> {
+        self.session_ranges
+            .iter()
+            .map(|range| {
+                let analyzer = self.session_analyzer(range);
+                let (start_line, end_line) = self.source_lines(range);
+
+                Session {
+                    start_line,
+                    end_line,
+                    source: self.source(start_line).map(Path::to_path_buf),
+                    stopped: session::has_shutdown(&analyzer.lines),
+                    platform: analyzer.platform,
+                    version: analyzer.version(),
+                    plugins: analyzer.loaded_plugins(plugins_limit),
+                    ports: Ports {
+                        vanilla: analyzer.vanilla_ports(),
+                        plugins: analyzer.plugin_ports(ports_root, ports_limit),
+                        mods: analyzer.mod_ports(ports_root, ports_limit),
+                    },
+                }
+            })
+            .collect()
+    }
+
+    pub fn build(
+        mut self,
+        plugins_limit: usize,
+        ports_limit: usize,
+    ) -> Result<DynamicAnalyzerDetails> {
+        let current_directory = std::env::current_dir()?;
         let ports_file_dir = current_directory.join("configuration").join("ports.toml");
 
-        let ports_file = std::fs::read_to_string(ports_file_dir.as_path()).unwrap();
-        let ports_root: PortsRoot = toml::from_str(ports_file.as_str()).unwrap();
+        let ports_file = std::fs::read_to_string(ports_file_dir.as_path())?;
+        let ports_root: PortsRoot =
+            toml::from_str(ports_file.as_str()).map_err(|source| Error::Config {
+                path: ports_file_dir,
+                source,
+            })?;
+
+        let custom_levels =
+            custom_levels(&current_directory.join("configuration").join("levels.toml"))?;
 
-        let parser = Parser::new(self.lines.clone(), vec![]);
+        let sessions = self.sessions(&ports_root, plugins_limit, ports_limit);
 
-        DynamicAnalyzerDetails {
-            chunks: parser.get_chunks(),
-            plugins: self.plugins(plugins_limit),
+        // Structured logs were parsed by `new` already
+        let entries = match self.structured.take() {
+            Some(mut structured) => {
+                apply_custom_levels(&mut structured.entries, &custom_levels);
+                structured.entries
+            }
+            None => Parser::iter(self.lines.iter().cloned(), custom_levels).collect(),
+        };
+        let thread_dumps = ThreadDump::find_all(&entries);
+
+        // An error may come from a plugin of any session
+        let all_plugins = self.loaded_plugins(plugins_limit);
+
+        // What the server runs now, after any upgrade partway through the log. The latest
+        // session may not have logged everything, e.g. when the log starts partway through it
+        let latest =
+            |logged: fn(&Session) -> bool| sessions.iter().rev().find(|session| logged(session));
+
+        let version = latest(|session| session.version.is_some())
+            .and_then(|session| session.version.clone())
+            .or_else(|| self.version());
+        let plugins = latest(|session| !session.plugins.is_empty())
+            .map_or_else(HashMap::new, |session| session.plugins.clone());
+        let ports = Ports {
+            vanilla: latest(|session| session.ports.vanilla.server.is_some()).map_or_else(
+                || self.vanilla_ports(),
+                |session| session.ports.vanilla.clone(),
+            ),
+            plugins: latest(|session| !session.ports.plugins.is_empty())
+                .map_or_else(HashMap::new, |session| session.ports.plugins.clone()),
+            mods: latest(|session| !session.ports.mods.is_empty())
+                .map_or_else(HashMap::new, |session| session.ports.mods.clone()),
+        };
+
+        Ok(DynamicAnalyzerDetails {
+            culprits: culprit::suspects(&entries, &thread_dumps, &all_plugins),
+            hang: thread_dumps
+                .last()
+                .and_then(|dump| culprit::hang_suspect(dump, &all_plugins)),
+            thread_dumps,
+            chunks: entries.iter().map(|entry| entry.to_string()).collect(),
+            stack_traces: entries
+                .into_iter()
+                .filter_map(|entry| entry.stack_trace)
+                .collect(),
+            plugins,
+            mods: self.mods(),
             platform: self.platform,
-            version: self.version(),
+            version,
+            java_version: self.java_version(),
             is_modded: self.is_modded(),
             is_proxy: self.is_proxy(),
             is_bukkit_based: self.is_bukkit_based(),
-            ports: Ports {
-                vanilla: self.vanilla_ports(),
-                plugins: self.plugin_ports(&ports_root, ports_limit),
-                mods: self.mod_ports(&ports_root, ports_limit),
-            },
-        }
+            ports,
+            sessions,
+            crash_report: self.crash_report,
+            fatal_error: self.fatal_error,
+        })
     }
 }
 
+/// Results of [`Analyzer::build`].
+///
+/// The platform, version, plugins and ports are those of the latest session that logged
+/// them, the server's current setup. The rest covers the whole log.
 #[derive(Serialize, Clone, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct DynamicAnalyzerDetails {
     #[serde(skip_serializing)]
     pub chunks: Vec<String>,
+    #[serde(skip_serializing)]
+    pub stack_traces: Vec<StackTrace>,
     pub plugins: HashMap<String, String>,
+    pub mods: HashMap<String, String>,
+    /// Plugins and mods most likely behind the log's errors, most likely first
+    pub culprits: Vec<Suspect>,
+    /// Watchdog thread dumps of a frozen server, in order
+    pub thread_dumps: Vec<ThreadDump>,
+    /// What the `Server thread` was stuck on in the last thread dump
+    pub hang: Option<Suspect>,
     pub platform: Platform,
     pub version: Option<String>,
+    pub java_version: Option<String>,
     pub is_modded: bool,
     pub is_proxy: bool,
     pub is_bukkit_based: bool,
     pub ports: Ports,
+    /// Each server start in the log, oldest first
+    pub sessions: Vec<Session>,
+    pub crash_report: Option<CrashReport>,
+    pub fatal_error: Option<FatalErrorLog>,
 }
 
 fn determine_platform(lines: &[String]) -> Platform {
@@ -310,6 +647,54 @@ fn determine_platform(lines: &[String]) -> Platform {
     Platform::Vanilla
 }
 
+/// Platform from the crash report's server brand, or the kind of mod list it has.
+fn crash_report_platform(crash_report: &CrashReport) -> Option<Platform> {
+    let platform = match crash_report.brand().map(str::to_lowercase).as_deref() {
+        Some("forge" | "neoforge") => Some(Platform::Forge),
+        Some("fabric" | "quilt") => Some(Platform::Fabric),
+        Some("paper") => Some(Platform::Paper),
+        Some("purpur") => Some(Platform::Purpur),
+        Some("pufferfish") => Some(Platform::Pufferfish),
+        Some("spigot") => Some(Platform::Spigot),
+        Some("craftbukkit") => Some(Platform::CraftBukkit),
+        _ => None,
+    };
+
+    platform.or_else(|| {
+        if crash_report.system_detail("Fabric Mods").is_some() {
+            Some(Platform::Fabric)
+        } else if crash_report.system_detail("FML").is_some()
+            || crash_report.system_detail("Mod List").is_some()
+        {
+            Some(Platform::Forge)
+        } else {
+            None
+        }
+    })
+}
+
+#[derive(Deserialize, Debug)]
+struct LevelsRoot {
+    levels: Vec<CustomLevel>,
+}
+
+/// Custom levels from the configuration, the file is optional.
+fn custom_levels(path: &Path) -> Result<Vec<CustomLevel>> {
+    let levels_file = match std::fs::read_to_string(path) {
+        Ok(levels_file) => levels_file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+
+    let levels_root: LevelsRoot =
+        toml::from_str(levels_file.as_str()).map_err(|source| Error::Config {
+            path: path.to_path_buf(),
+            source,
+        })?;
+
+    Ok(levels_root.levels)
+}
+
 #[derive(Deserialize, Debug)]
 struct PortsRoot {
     ports: PluginModPorts,
@@ -326,3 +711,83 @@ pub struct Plugin {
     pub name: String,
     pub version: String,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sessions(fixture: &str) -> Vec<(usize, usize)> {
+        let lines: Vec<String> = fixture.lines().map(String::from).collect();
+        let ports_root = PortsRoot {
+            ports: PluginModPorts {
+                plugins: HashMap::new(),
+                mods: HashMap::new(),
+            },
+        };
+
+        Analyzer::new(&lines)
+            .unwrap()
+            .sessions(&ports_root, usize::MAX, usize::MAX)
+            .iter()
+            .map(|session| (session.start_line, session.end_line))
+            .collect()
+    }
+
+    #[test]
+    fn proxy_sessions() {
+        let log = "[18:02:11 INFO]: Booting up Velocity 3.3.0-SNAPSHOT (git-6a05fe0b-b368)...\n\
+                   [18:02:11 INFO]: Loading localizations...\n\
+                   [18:02:12 INFO]: Loaded plugin luckperms 5.4.117 by Luck\n\
+                   [18:02:12 INFO]: Listening on /[0:0:0:0:0:0:0:0%0]:25577\n\
+                   [18:02:12 INFO]: Done (1.42s)!\n\
+                   [19:40:03 INFO]: Shutting down the proxy...\n\
+                   [19:40:03 INFO]: Closing endpoint /[0:0:0:0:0:0:0:0%0]:25577";
+        let lines: Vec<String> = log.lines().map(String::from).collect();
+        let analyzer = Analyzer::new(&lines).unwrap();
+
+        assert!(matches!(analyzer.platform, Platform::Velocity));
+        assert!(analyzer.loaded_plugins(usize::MAX).is_empty());
+        assert_eq!(sessions(log), [(1, 7)]);
+    }
+
+    #[test]
+    fn sourced_lines() {
+        let sourced = |source: &Arc<Path>, line: &str| SourcedLine {
+            source: source.clone(),
+            line: line.to_string(),
+        };
+        let rotated: Arc<Path> = Path::new("logs/2024-01-12-1.log.gz").into();
+        let latest: Arc<Path> = Path::new("logs/latest.log").into();
+
+        let analyzer = Analyzer::from_sourced_lines(&[
+            sourced(&rotated, "[12:00:00] [Server thread/INFO]: Starting minecraft server version 1.20.2"),
+            sourced(&rotated, "[12:00:05] [Server thread/INFO]: Done (5.0s)!"),
+            sourced(&latest, "[13:00:00] [ServerMain/INFO]: Environment: Environment[sessionHost=https://sessionserver.mojang.com]"),
+            sourced(&latest, "[13:00:01] [Server thread/INFO]: Starting minecraft server version 1.20.4"),
+        ])
+        .unwrap();
+
+        assert_eq!(analyzer.sources.len(), 2);
+        assert_eq!(analyzer.sources[1].lines, 2..4);
+        assert_eq!(analyzer.source(2), Some(rotated.as_ref()));
+        assert_eq!(analyzer.source(3), Some(latest.as_ref()));
+        assert_eq!(analyzer.source(5), None);
+        assert_eq!(analyzer.session_ranges, [0..2, 2..4]);
+    }
+
+    #[test]
+    fn structured_sessions_have_source_lines() {
+        assert_eq!(
+            sessions(include_str!("../../tests/fixtures/layouts/json.json")),
+            [(2, 80), (81, 94)]
+        );
+        assert_eq!(
+            sessions(include_str!("../../tests/fixtures/layouts/xml.xml")),
+            [(4, 28), (30, 33)]
+        );
+        assert_eq!(
+            sessions(include_str!("../../tests/fixtures/layouts/ecs.json")),
+            [(1, 4), (5, 5)]
+        );
+    }
+}
@@ -0,0 +1,182 @@
+use super::{Platform, Ports};
+use regex::Regex;
+use serde::Serialize;
+use std::{collections::HashMap, ops::Range, path::PathBuf, sync::LazyLock};
+
+// Lines printed once per boot, in roughly this order
+const STARTUP_MARKERS: &[&str] = &[
+    "ModLauncher running: args",
+    "with Fabric Loader",
+    "Loading libraries, please wait",
+    "Environment: authHost",
+    "Environment: Environment[",
+    "Starting minecraft server version",
+    "Booting up Velocity",
+    "Enabled BungeeCord version",
+    "Enabled Waterfall version",
+];
+
+// Vanilla and Bukkit-based servers, Velocity, BungeeCord and Waterfall, logged by the
+// server itself right after the level, so chat and plugin messages quoting them don't count:
+// [12:30:00] [Server thread/INFO]: Stopping server
+// [12:30:00] [Server thread/INFO] [minecraft/DedicatedServer]: Stopping server
+// [12:30:00] [Server thread/INFO] (Minecraft) Stopping server
+// [12:30:00 INFO]: Shutting down the proxy...
+// 12:30:00 [INFO] Closing listeners
+static SHUTDOWN_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"^[^<]*?[/ \[]INFO\](?: \[[^\[\]]*\]| \([^()]*\))?:? (?:Stopping server|Shutting down the proxy|Closing listeners)",
+    )
+    .unwrap_or_else(|e| panic!("Failed to create 'SHUTDOWN_REGEX': {}", e))
+});
+
+/// One run of the server, from its startup to its shutdown or the next startup.
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Session {
+    /// 1-based number of the session's first line
+    pub start_line: usize,
+    /// 1-based number of the session's last line, inclusive
+    pub end_line: usize,
+    /// File the session starts in, for logs read from several files
+    pub source: Option<PathBuf>,
+    /// Whether the server shut down cleanly, instead of crashing or being killed
+    pub stopped: bool,
+    pub platform: Platform,
+    pub version: Option<String>,
+    pub plugins: HashMap<String, String>,
+    pub ports: Ports,
+}
+
+/// Splits a log into the line ranges of its sessions.
+///
+/// A session starts at the first startup marker after a shutdown, or at a startup marker it
+/// has already seen when the server restarted without stopping. Lines before the first
+/// startup belong to the first session, a log may start partway through one.
+///
+/// `file_starts` are the first lines of the files the log was read from, in order. Servers
+/// rotate their log when they start, so the first startup in a file starts a session at the
+/// file's first line. A file without one continues the previous session, e.g. after a daily
+/// rotation.
+pub(crate) fn session_ranges(lines: &[String], file_starts: &[usize]) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    let mut start = 0;
+    let mut seen: Vec<&str> = Vec::new();
+    let mut stopped = false;
+    let mut file_starts = file_starts.iter().copied().peekable();
+    // Start of the current file, until its first startup marker or shutdown
+    let mut file_start = None;
+
+    for (idx, line) in lines.iter().enumerate() {
+        while file_starts
+            .next_if(|file_start| *file_start <= idx)
+            .is_some()
+        {
+            file_start = Some(idx);
+        }
+
+        if is_shutdown(line) {
+            stopped = true;
+            file_start = None;
+            continue;
+        }
+
+        let Some(marker) = startup_marker(line) else {
+            continue;
+        };
+
+        let split = match file_start.take() {
+            Some(file_start) => Some(file_start),
+            None if stopped || seen.contains(&marker) => Some(idx),
+            None => None,
+        };
+
+        if let Some(split) = split.filter(|split| *split > start) {
+            ranges.push(start..split);
+            start = split;
+            seen.clear();
+            stopped = false;
+        }
+
+        seen.push(marker);
+    }
+
+    ranges.push(start..lines.len());
+    ranges
+}
+
+pub(crate) fn has_shutdown(lines: &[String]) -> bool {
+    lines.iter().any(|line| is_shutdown(line))
+}
+
+fn is_shutdown(line: &str) -> bool {
+    SHUTDOWN_REGEX.is_match(line)
+}
+
+fn startup_marker(line: &str) -> Option<&'static str> {
+    STARTUP_MARKERS
+        .iter()
+        .find(|marker| line.contains(*marker))
+        .copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(log: &str) -> Vec<String> {
+        log.lines().map(String::from).collect()
+    }
+
+    #[test]
+    fn restarts() {
+        let lines = lines(
+            "[12:00:00] [Server thread/INFO]: Starting minecraft server version 1.20.4\n\
+             [12:30:00] [Server thread/INFO]: Stopping server\n\
+             [12:30:01] [Server thread/INFO]: Saving worlds\n\
+             [12:31:00] [Server thread/INFO]: Starting minecraft server version 1.20.4\n\
+             [13:00:00] [Server thread/INFO]: Starting minecraft server version 1.20.4",
+        );
+
+        assert_eq!(session_ranges(&lines, &[]), [0..3, 3..4, 4..5]);
+    }
+
+    #[test]
+    fn shutdown_markers() {
+        for line in [
+            "[12:30:00] [Server thread/INFO]: Stopping server",
+            "[12:30:00] [Server thread/INFO] [minecraft/DedicatedServer]: Stopping server",
+            "[12:30:00] [Server thread/INFO] (Minecraft) Stopping server",
+            "[12:30:00 INFO]: Stopping server",
+            "[12:30:00 INFO]: Shutting down the proxy...",
+            "12:30:00 [INFO] Closing listeners",
+        ] {
+            assert!(is_shutdown(line), "{line}");
+        }
+
+        for line in [
+            "[12:30:00] [Server thread/INFO]: <Steve> Stopping server in 5 minutes",
+            "[12:30:00] [Server thread/INFO]: [Essentials] Stopping server soon",
+            "[12:30:00] [Server thread/INFO]: Steve issued server command: /say Stopping server",
+            "[12:30:00] [Async Chat Thread - #3/INFO]: [Not Secure] <Steve> Closing listeners?",
+        ] {
+            assert!(!is_shutdown(line), "{line}");
+        }
+    }
+
+    #[test]
+    fn file_boundaries() {
+        let lines = lines(
+            "[12:00:00] [Server thread/INFO]: Starting minecraft server version 1.20.4\n\
+             [12:30:00] [Server thread/INFO]: Done (2.1s)!\n\
+             [00:00:01] [Server thread/INFO]: Still running after a daily rotation\n\
+             [00:10:00] [main/INFO]: Environment: Environment[sessionHost=https://sessionserver.mojang.com]\n\
+             [00:10:01] [Server thread/INFO]: Starting minecraft server version 1.20.4",
+        );
+
+        // The server crashed in the second file, the third starts with the restart
+        assert_eq!(session_ranges(&lines, &[0, 2, 3]), [0..3, 3..5]);
+        // Without files, only the repeated marker tells
+        assert_eq!(session_ranges(&lines, &[0]), [0..4, 4..5]);
+    }
+}
@@ -146,10 +146,10 @@ impl StaticAnalyzer {
     }
 }
 
-struct PluginNameNormalizer<'a>(&'a str);
+pub(crate) struct PluginNameNormalizer<'a>(pub(crate) &'a str);
 
 impl PluginNameNormalizer<'_> {
-    fn clear(&self) -> String {
+    pub(crate) fn clear(&self) -> String {
         self.0.to_lowercase().replace(['-', '_', ' '], "")
     }
 }
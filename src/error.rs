@@ -0,0 +1,27 @@
+use std::{io, path::PathBuf};
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("log is empty")]
+    EmptyLog,
+
+    #[error("invalid configuration in '{}': {source}", path.display())]
+    Config {
+        path: PathBuf,
+        source: toml::de::Error,
+    },
+
+    #[error("script '{}' has no `///` section with its detections", path.display())]
+    MissingDetections { path: PathBuf },
+
+    #[error("failed to compile script '{}': {source}", path.display())]
+    ScriptCompile {
+        path: PathBuf,
+        source: rhai::ParseError,
+    },
+
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
@@ -1,3 +1,6 @@
 pub mod analyzer;
+mod error;
 pub mod log;
 pub mod parser;
+
+pub use error::{Error, Result};
@@ -0,0 +1,276 @@
+use encoding_rs::{Encoding, UTF_16BE, UTF_16LE, UTF_8, WINDOWS_1252};
+use serde::Serialize;
+use std::{
+    io,
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+use tokio::io::AsyncBufRead;
+
+// Number of leading bytes inspected when looking for BOM-less UTF-16
+const SNIFF_LENGTH: usize = 512;
+
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum DecodeMode {
+    /// Lines that aren't valid in the detected encoding are decoded with the fallback
+    /// (legacy) encoding and reported as warnings.
+    #[default]
+    Lossy,
+    /// The first line that isn't valid in the detected encoding fails the read.
+    Strict,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DecodeWarning {
+    /// 1-based line number
+    pub line: usize,
+    pub kind: DecodeWarningKind,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum DecodeWarningKind {
+    /// The line wasn't valid in the log's encoding and was decoded as `encoding`.
+    Fallback { encoding: &'static str },
+    /// The line contained sequences that couldn't be decoded and were replaced with U+FFFD.
+    Replaced { encoding: &'static str },
+}
+
+impl std::fmt::Display for DecodeWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.kind {
+            DecodeWarningKind::Fallback { encoding } => {
+                write!(f, "line {} decoded as {}", self.line, encoding)
+            }
+            DecodeWarningKind::Replaced { encoding } => write!(
+                f,
+                "line {} contains invalid {} sequences",
+                self.line, encoding
+            ),
+        }
+    }
+}
+
+/// Splits raw bytes into lines and decodes them.
+///
+/// The encoding is detected from the first bytes of the input: a BOM (UTF-8, UTF-16LE/BE),
+/// BOM-less UTF-16 by its zero bytes, otherwise UTF-8. Logs written by Windows hosts often
+/// mix UTF-8 with lines in the system codepage, so invalid UTF-8 lines are decoded with the
+/// fallback encoding instead of ending the log.
+pub(crate) struct LineDecoder {
+    encoding: Option<&'static Encoding>,
+    fallback: &'static Encoding,
+    mode: DecodeMode,
+    hold_partial: bool,
+    pending: Vec<u8>,
+    scanned: usize,
+    line: usize,
+    warnings: Vec<DecodeWarning>,
+}
+
+impl LineDecoder {
+    pub(crate) fn new() -> Self {
+        Self {
+            encoding: None,
+            fallback: WINDOWS_1252,
+            mode: DecodeMode::default(),
+            hold_partial: false,
+            pending: Vec::new(),
+            scanned: 0,
+            line: 0,
+            warnings: Vec::new(),
+        }
+    }
+
+    pub(crate) fn set_encoding(&mut self, encoding: &'static Encoding) {
+        self.encoding = Some(encoding);
+    }
+
+    pub(crate) fn set_fallback(&mut self, encoding: &'static Encoding) {
+        self.fallback = encoding;
+    }
+
+    pub(crate) fn set_mode(&mut self, mode: DecodeMode) {
+        self.mode = mode;
+    }
+
+    /// Keeps an unterminated last line until the rest of it arrives, for files that are
+    /// still being written.
+    pub(crate) fn set_hold_partial(&mut self, hold_partial: bool) {
+        self.hold_partial = hold_partial;
+    }
+
+    pub(crate) fn encoding(&self) -> &'static Encoding {
+        self.encoding.unwrap_or(UTF_8)
+    }
+
+    pub(crate) fn take_warnings(&mut self) -> Vec<DecodeWarning> {
+        std::mem::take(&mut self.warnings)
+    }
+
+    pub(crate) fn poll_next_line<R: AsyncBufRead + Unpin>(
+        &mut self,
+        reader: &mut R,
+        cx: &mut Context<'_>,
+    ) -> Poll<io::Result<Option<String>>> {
+        loop {
+            if self.encoding.is_some() {
+                if let Some(end) = self.find_newline() {
+                    let newline_len = self.unit_len();
+                    let bytes: Vec<u8> = self.pending.drain(..end + newline_len).collect();
+                    self.scanned = 0;
+                    return Poll::Ready(self.decode(&bytes[..end]).map(Some));
+                }
+            }
+
+            let buf = ready!(Pin::new(&mut *reader).poll_fill_buf(cx))?;
+
+            if buf.is_empty() {
+                if self.encoding.is_none() && !self.pending.is_empty() {
+                    self.detect();
+                    continue;
+                }
+
+                if self.pending.is_empty() || self.hold_partial {
+                    return Poll::Ready(Ok(None));
+                }
+
+                return Poll::Ready(self.flush_partial().transpose());
+            }
+
+            let len = buf.len();
+            self.pending.extend_from_slice(buf);
+            Pin::new(&mut *reader).consume(len);
+
+            if self.encoding.is_none() && self.pending.len() >= SNIFF_LENGTH {
+                self.detect();
+            }
+        }
+    }
+
+    /// Decodes whatever is left of an unterminated line.
+    pub(crate) fn flush_partial(&mut self) -> Option<io::Result<String>> {
+        if self.pending.is_empty() {
+            return None;
+        }
+
+        if self.encoding.is_none() {
+            self.detect();
+        }
+
+        let bytes = std::mem::take(&mut self.pending);
+        self.scanned = 0;
+        Some(self.decode(&bytes))
+    }
+
+    fn detect(&mut self) {
+        if let Some((encoding, bom_length)) = Encoding::for_bom(&self.pending) {
+            self.pending.drain(..bom_length);
+            self.encoding = Some(encoding);
+            return;
+        }
+
+        let sample = &self.pending[..self.pending.len().min(SNIFF_LENGTH) & !1];
+        let pairs = sample.len() / 2;
+
+        if pairs > 0 {
+            let even_zeros = sample.iter().step_by(2).filter(|&&b| b == 0).count();
+            let odd_zeros = sample
+                .iter()
+                .skip(1)
+                .step_by(2)
+                .filter(|&&b| b == 0)
+                .count();
+
+            // ASCII text in UTF-16 has a zero byte in (almost) every code unit
+            if odd_zeros * 10 >= pairs * 4 && even_zeros * 20 < pairs {
+                self.encoding = Some(UTF_16LE);
+                return;
+            }
+            if even_zeros * 10 >= pairs * 4 && odd_zeros * 20 < pairs {
+                self.encoding = Some(UTF_16BE);
+                return;
+            }
+        }
+
+        self.encoding = Some(UTF_8);
+    }
+
+    fn unit_len(&self) -> usize {
+        match self.encoding() {
+            encoding if encoding == UTF_16LE || encoding == UTF_16BE => 2,
+            _ => 1,
+        }
+    }
+
+    fn find_newline(&mut self) -> Option<usize> {
+        let encoding = self.encoding();
+
+        let newline: &[u8] = if encoding == UTF_16LE {
+            &[b'\n', 0]
+        } else if encoding == UTF_16BE {
+            &[0, b'\n']
+        } else {
+            b"\n"
+        };
+
+        let position = self.pending[self.scanned..]
+            .chunks_exact(newline.len())
+            .position(|unit| unit == newline)
+            .map(|position| self.scanned + position * newline.len());
+
+        if position.is_none() {
+            self.scanned = self.pending.len() - self.pending.len() % newline.len();
+        }
+
+        position
+    }
+
+    fn decode(&mut self, bytes: &[u8]) -> io::Result<String> {
+        self.line += 1;
+
+        let encoding = self.encoding();
+
+        let (decoded, had_errors) = encoding.decode_without_bom_handling(bytes);
+        let mut decoded = decoded.into_owned();
+
+        if had_errors {
+            match self.mode {
+                DecodeMode::Strict => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("line {} is not valid {}", self.line, encoding.name()),
+                    ))
+                }
+                DecodeMode::Lossy => {
+                    let kind = if encoding == UTF_8 {
+                        // Single-byte codepages map every byte, so this can't fail again
+                        let (fallback, _) = self.fallback.decode_without_bom_handling(bytes);
+                        decoded = fallback.into_owned();
+                        DecodeWarningKind::Fallback {
+                            encoding: self.fallback.name(),
+                        }
+                    } else {
+                        DecodeWarningKind::Replaced {
+                            encoding: encoding.name(),
+                        }
+                    };
+
+                    let warning = DecodeWarning {
+                        line: self.line,
+                        kind,
+                    };
+                    log::warn!("{}", warning);
+                    self.warnings.push(warning);
+                }
+            }
+        }
+
+        if decoded.ends_with('\r') {
+            decoded.pop();
+        }
+
+        Ok(decoded)
+    }
+}
@@ -0,0 +1,202 @@
+use super::encoding::LineDecoder;
+use crate::parser::{
+    log_level::CustomLevel,
+    parser::{EntryGrouper, LogEntry},
+};
+use crate::Result;
+use chrono::Local;
+use std::{
+    future::poll_fn,
+    io::{self, SeekFrom},
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+use tokio::{
+    fs::File,
+    io::{AsyncSeekExt, BufReader},
+};
+
+#[derive(Clone, Debug)]
+pub struct FollowOptions {
+    /// Read the lines already in the file instead of starting at its end like `tail -f`
+    pub from_start: bool,
+    /// How often the file is checked for new lines and rotation
+    pub poll_interval: Duration,
+    /// How long an entry may stay without new lines before it's considered complete,
+    /// see [`EntryFollower`]
+    pub flush_after: Duration,
+}
+
+impl Default for FollowOptions {
+    fn default() -> Self {
+        Self {
+            from_start: false,
+            poll_interval: Duration::from_millis(250),
+            flush_after: Duration::from_millis(500),
+        }
+    }
+}
+
+/// Identifies the file behind a path, so replacing `latest.log` can be told apart from
+/// appending to it.
+#[derive(Clone, Copy, PartialEq, Debug)]
+struct FileIdentity(Option<(u64, u64)>);
+
+impl FileIdentity {
+    #[cfg(unix)]
+    fn of(metadata: &std::fs::Metadata) -> Self {
+        use std::os::unix::fs::MetadataExt;
+        FileIdentity(Some((metadata.dev(), metadata.ino())))
+    }
+
+    // Without inodes a replaced file is only noticed once it's shorter than the old one
+    #[cfg(not(unix))]
+    fn of(_metadata: &std::fs::Metadata) -> Self {
+        FileIdentity(None)
+    }
+}
+
+/// Follows a log that's still being written, like `tail -F`.
+///
+/// Partially written lines are held back until they're complete. When the server rotates
+/// the log (the file is truncated, or replaced by a new `latest.log`), reading continues
+/// from the start of the new file.
+pub struct LogFollower {
+    path: PathBuf,
+    options: FollowOptions,
+    reader: BufReader<File>,
+    identity: FileIdentity,
+    decoder: LineDecoder,
+}
+
+impl LogFollower {
+    pub async fn open(path: impl AsRef<Path>, options: FollowOptions) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+
+        let mut file = File::open(&path).await?;
+        let identity = FileIdentity::of(&file.metadata().await?);
+
+        if !options.from_start {
+            file.seek(SeekFrom::End(0)).await?;
+        }
+
+        Ok(Self {
+            path,
+            options,
+            reader: BufReader::new(file),
+            identity,
+            decoder: Self::decoder(),
+        })
+    }
+
+    fn decoder() -> LineDecoder {
+        let mut decoder = LineDecoder::new();
+        decoder.set_hold_partial(true);
+        decoder
+    }
+
+    /// Waits until the next complete line is written.
+    pub async fn next_line(&mut self) -> Result<String> {
+        loop {
+            if let Some(line) = self.try_next_line().await? {
+                return Ok(line);
+            }
+
+            tokio::time::sleep(self.options.poll_interval).await;
+        }
+    }
+
+    /// Next complete line, or `None` when there's nothing new yet.
+    pub async fn try_next_line(&mut self) -> Result<Option<String>> {
+        let line = poll_fn(|cx| self.decoder.poll_next_line(&mut self.reader, cx)).await?;
+
+        if line.is_some() {
+            return Ok(line);
+        }
+
+        self.check_rotation().await
+    }
+
+    /// Reopens or rewinds the log when it was rotated, returning the unterminated last line
+    /// of the old file, if any.
+    async fn check_rotation(&mut self) -> Result<Option<String>> {
+        let metadata = match tokio::fs::metadata(&self.path).await {
+            Ok(metadata) => metadata,
+            // Moved away, the new file doesn't exist yet
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+
+        let position = self.reader.get_mut().stream_position().await?;
+
+        let replaced = FileIdentity::of(&metadata) != self.identity;
+        let truncated = metadata.len() < position;
+
+        if !replaced && !truncated {
+            return Ok(None);
+        }
+
+        let partial = self.decoder.flush_partial().transpose()?;
+
+        if replaced {
+            let file = File::open(&self.path).await?;
+            self.identity = FileIdentity::of(&file.metadata().await?);
+            self.reader = BufReader::new(file);
+        } else {
+            self.reader.seek(SeekFrom::Start(0)).await?;
+        }
+
+        self.decoder = Self::decoder();
+
+        Ok(partial)
+    }
+
+    /// Groups followed lines into parsed entries, see [`EntryFollower`].
+    pub fn entries(self, custom_levels: Vec<CustomLevel>) -> EntryFollower {
+        let mut grouper = EntryGrouper::new(custom_levels);
+        // Followed logs are live, so time-only timestamps are from today
+        grouper.set_date(Local::now().date_naive());
+
+        EntryFollower {
+            follower: self,
+            grouper,
+            last_line: Instant::now(),
+        }
+    }
+}
+
+/// Parses a followed log into entries as lines are appended.
+///
+/// A multi-line entry (e.g. a stack trace) is complete once the next entry starts, but a live
+/// log may not get a next line for a while, so the last entry is also emitted once no lines
+/// were written for [`FollowOptions::flush_after`].
+pub struct EntryFollower {
+    follower: LogFollower,
+    grouper: EntryGrouper,
+    last_line: Instant,
+}
+
+impl EntryFollower {
+    pub async fn next_entry(&mut self) -> Result<LogEntry> {
+        loop {
+            match self.follower.try_next_line().await? {
+                Some(line) => {
+                    self.last_line = Instant::now();
+
+                    if let Some(entry) = self.grouper.push(line) {
+                        return Ok(entry);
+                    }
+                }
+                None => {
+                    if self.last_line.elapsed() >= self.follower.options.flush_after {
+                        if let Some(entry) = self.grouper.finish() {
+                            return Ok(entry);
+                        }
+                    }
+
+                    tokio::time::sleep(self.follower.options.poll_interval).await;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,204 @@
+use encoding_rs::Encoding;
+use std::io::{self, Cursor};
+use std::path::Path;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::{
+    fs::File,
+    io::{AsyncBufRead, BufReader, Lines},
+};
+use tokio_stream::{Stream, StreamExt};
+
+use self::encoding::{DecodeMode, DecodeWarning, LineDecoder};
+use self::reader::LogReader;
+use self::redaction::Redactor;
+use crate::parser::{log_level::CustomLevel, parser::Parser, stream::ParserStream};
+use crate::Result;
+
+pub mod encoding;
+pub mod follow;
+pub mod pseudonyms;
+pub mod reader;
+pub mod redaction;
+pub mod secrets;
+pub mod set;
+
+/// Line source for the parser and analyzer.
+///
+/// Can be built from any [`AsyncBufRead`] (files, in-memory buffers, request bodies,
+/// `tokio::io::stdin`, decompression streams, ...) or directly from `&str`/`String`.
+///
+/// The text encoding is detected from the input, see [`encoding`].
+pub struct Log<R> {
+    reader: R,
+    decoder: LineDecoder,
+}
+
+pub struct DecodedLog {
+    pub lines: Vec<String>,
+    pub encoding: &'static str,
+    pub warnings: Vec<DecodeWarning>,
+}
+
+impl<R: AsyncBufRead + Unpin> Log<R> {
+    pub fn new(lines: Lines<R>) -> Self {
+        Self::from_reader(lines.into_inner())
+    }
+
+    pub fn from_reader(reader: R) -> Self {
+        Self {
+            reader,
+            decoder: LineDecoder::new(),
+        }
+    }
+
+    /// Skips detection and decodes the log as `encoding`.
+    pub fn with_encoding(mut self, encoding: &'static Encoding) -> Self {
+        self.decoder.set_encoding(encoding);
+        self
+    }
+
+    /// Encoding used for lines that aren't valid UTF-8, `windows-1252` by default.
+    pub fn with_fallback_encoding(mut self, encoding: &'static Encoding) -> Self {
+        self.decoder.set_fallback(encoding);
+        self
+    }
+
+    pub fn with_decode_mode(mut self, mode: DecodeMode) -> Self {
+        self.decoder.set_mode(mode);
+        self
+    }
+
+    /// Reads the whole log, reporting lines that couldn't be decoded cleanly.
+    pub async fn decode(mut self) -> Result<DecodedLog> {
+        let mut lines = Vec::new();
+
+        while let Some(line) = self.next().await {
+            lines.push(line?);
+        }
+
+        Ok(DecodedLog {
+            lines,
+            encoding: self.decoder.encoding().name(),
+            warnings: self.decoder.take_warnings(),
+        })
+    }
+
+    pub async fn lines(self) -> Result<Vec<String>> {
+        let mut lines = Vec::new();
+
+        let mut lines_stream = self;
+
+        while let Some(line) = lines_stream.next().await {
+            lines.push(line?);
+        }
+
+        Ok(lines)
+    }
+
+    pub async fn first_n_lines(self, limit: usize) -> Result<Vec<String>> {
+        let mut lines = Vec::new();
+
+        let mut lines_stream = self.take(limit);
+
+        while let Some(line) = lines_stream.next().await {
+            lines.push(line?);
+        }
+
+        Ok(lines)
+    }
+
+    /// Streams parsed entries without reading the whole log first, see [`Parser::stream`].
+    pub fn entries(self, custom_levels: Vec<CustomLevel>) -> ParserStream<Self> {
+        Parser::stream(self, custom_levels)
+    }
+
+    /// Reads the whole log with IPs removed, see [`RedactionPolicy`](redaction::RedactionPolicy).
+    pub async fn lines_hideips(self) -> Result<Vec<String>> {
+        self.lines_redacted(&mut Redactor::default()).await
+    }
+
+    pub async fn first_n_lines_hideips(self, limit: usize) -> Result<Vec<String>> {
+        self.first_n_lines_redacted(&mut Redactor::default(), limit)
+            .await
+    }
+
+    /// Pseudonyms handed out along the way stay available through [`Redactor::pseudonyms`].
+    pub async fn lines_redacted(self, redactor: &mut Redactor) -> Result<Vec<String>> {
+        let mut lines = Vec::new();
+
+        let mut lines_stream = self.redacted(redactor);
+
+        while let Some(line) = lines_stream.next().await {
+            lines.push(line?);
+        }
+
+        Ok(lines)
+    }
+
+    pub async fn first_n_lines_redacted(
+        self,
+        redactor: &mut Redactor,
+        limit: usize,
+    ) -> Result<Vec<String>> {
+        let mut lines = Vec::new();
+
+        let mut lines_stream = self.redacted(redactor).take(limit);
+
+        while let Some(line) = lines_stream.next().await {
+            lines.push(line?);
+        }
+
+        Ok(lines)
+    }
+
+    /// Streams the log with every line passed through `redactor`.
+    pub fn redacted<'a>(
+        self,
+        redactor: &'a mut Redactor,
+    ) -> impl Stream<Item = io::Result<String>> + 'a
+    where
+        R: 'a,
+    {
+        self.map(move |line| line.map(|line| redactor.redact(line)))
+    }
+}
+
+impl<R: AsyncBufRead + Unpin> Stream for Log<R> {
+    type Item = io::Result<String>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        this.decoder
+            .poll_next_line(&mut this.reader, cx)
+            .map(io::Result::transpose)
+    }
+}
+
+impl Log<LogReader> {
+    /// Builds a log from a reader that may be gzip compressed, see [`LogReader::detect`].
+    pub async fn detect<R>(reader: R) -> Result<Self>
+    where
+        R: AsyncBufRead + Unpin + Send + 'static,
+    {
+        Ok(Self::from_reader(LogReader::detect(reader).await?))
+    }
+
+    /// Opens `latest.log` as well as rotated `*.log.gz` archives.
+    pub async fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let file = File::open(path).await?;
+        Self::detect(BufReader::new(file)).await
+    }
+}
+
+impl<'a> From<&'a str> for Log<&'a [u8]> {
+    fn from(content: &'a str) -> Self {
+        Self::from_reader(content.as_bytes())
+    }
+}
+
+impl From<String> for Log<Cursor<Vec<u8>>> {
+    fn from(content: String) -> Self {
+        Self::from_reader(Cursor::new(content.into_bytes()))
+    }
+}
@@ -0,0 +1,81 @@
+use serde::Serialize;
+use std::collections::HashMap;
+
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum PseudonymKind {
+    Ip,
+    Host,
+    Player,
+    Uuid,
+}
+
+impl PseudonymKind {
+    fn name(&self) -> &'static str {
+        match self {
+            PseudonymKind::Ip => "ip",
+            PseudonymKind::Host => "host",
+            PseudonymKind::Player => "player",
+            PseudonymKind::Uuid => "uuid",
+        }
+    }
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct Pseudonym {
+    pub token: String,
+    pub kind: PseudonymKind,
+    pub original: String,
+}
+
+/// Stable tokens (`{ip#3}`, `{player#12}`, ...) for values removed from a single log.
+///
+/// The same value always gets the same token, so lines coming from the same client can
+/// still be correlated. Only hand the mapping to people allowed to see the originals.
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct Pseudonyms {
+    entries: Vec<Pseudonym>,
+    #[serde(skip_serializing)]
+    index: HashMap<(PseudonymKind, String), usize>,
+    #[serde(skip_serializing)]
+    counters: HashMap<PseudonymKind, usize>,
+}
+
+impl Pseudonyms {
+    /// Token for `original`, `key` is the normalized form used to tell values apart.
+    pub(crate) fn token(&mut self, kind: PseudonymKind, key: &str, original: &str) -> String {
+        if let Some(&idx) = self.index.get(&(kind, key.to_string())) {
+            return self.entries[idx].token.clone();
+        }
+
+        let counter = self.counters.entry(kind).or_insert(0);
+        *counter += 1;
+
+        let token = format!("{{{}#{}}}", kind.name(), counter);
+
+        self.index
+            .insert((kind, key.to_string()), self.entries.len());
+        self.entries.push(Pseudonym {
+            token: token.clone(),
+            kind,
+            original: original.to_string(),
+        });
+
+        token
+    }
+
+    pub fn entries(&self) -> &[Pseudonym] {
+        &self.entries
+    }
+
+    pub fn original(&self, token: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|entry| entry.token == token)
+            .map(|entry| entry.original.as_str())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
@@ -0,0 +1,73 @@
+use async_compression::tokio::bufread::GzipDecoder;
+use std::{
+    io,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncRead, BufReader, ReadBuf};
+
+// Every gzip member starts with these two bytes (RFC 1952), regardless of file name
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Compression {
+    None,
+    Gzip,
+}
+
+/// Reader that transparently decompresses its input when needed.
+///
+/// Rotated Minecraft logs (`logs/YYYY-MM-DD-N.log.gz`) are gzip archives, so the input
+/// is sniffed for the gzip magic bytes and decompressed as a stream.
+pub struct LogReader {
+    inner: Pin<Box<dyn AsyncBufRead + Send>>,
+    compression: Compression,
+}
+
+impl LogReader {
+    pub async fn detect<R>(mut reader: R) -> io::Result<Self>
+    where
+        R: AsyncBufRead + Unpin + Send + 'static,
+    {
+        let compression = match reader.fill_buf().await?.starts_with(&GZIP_MAGIC) {
+            true => Compression::Gzip,
+            false => Compression::None,
+        };
+
+        let inner: Pin<Box<dyn AsyncBufRead + Send>> = match compression {
+            Compression::Gzip => {
+                let mut decoder = GzipDecoder::new(reader);
+                // `gzip -c a.log b.log` and some panel exports produce multi-member archives
+                decoder.multiple_members(true);
+                Box::pin(BufReader::new(decoder))
+            }
+            Compression::None => Box::pin(reader),
+        };
+
+        Ok(Self { inner, compression })
+    }
+
+    pub fn compression(&self) -> Compression {
+        self.compression
+    }
+}
+
+impl AsyncRead for LogReader {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        self.inner.as_mut().poll_read(cx, buf)
+    }
+}
+
+impl AsyncBufRead for LogReader {
+    fn poll_fill_buf(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<&[u8]>> {
+        self.get_mut().inner.as_mut().poll_fill_buf(cx)
+    }
+
+    fn consume(mut self: Pin<&mut Self>, amt: usize) {
+        self.inner.as_mut().consume(amt)
+    }
+}
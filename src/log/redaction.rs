@@ -0,0 +1,618 @@
+use super::pseudonyms::{PseudonymKind, Pseudonyms};
+use super::secrets::{SecretReport, SecretScrubber};
+use crate::analyzer::static_analyzer::StaticAnalyzer;
+use regex::{Captures, Regex};
+use serde::Deserialize;
+use std::{
+    collections::HashSet,
+    net::{Ipv4Addr, Ipv6Addr},
+    sync::LazyLock,
+};
+
+// 1.2.3.4, 1.2.3.4:25565
+static IPV4_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(\d{1,3}\.\d{1,3}\.\d{1,3}\.\d{1,3})(?::(\d{1,5}))?")
+        .unwrap_or_else(|e| panic!("Failed to create 'IPV4_REGEX': {}", e))
+});
+
+// 2001:db8::1, [2001:db8::1]:25565, fe80::1%eth0, ::ffff:1.2.3.4
+// Candidates are validated by `Ipv6Addr`, so timestamps like 12:34:56 don't match
+static IPV6_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(\[)?((?:[0-9A-Fa-f]{0,4}:){2,7}(?:\d{1,3}\.\d{1,3}\.\d{1,3}\.\d{1,3}|[0-9A-Fa-f]{0,4})(?:%[0-9A-Za-z]+)?)(?:(\])(?::(\d{1,5}))?)?",
+    )
+    .unwrap_or_else(|e| panic!("Failed to create 'IPV6_REGEX': {}", e))
+});
+
+// play.example.com, mc.example.net:25566
+// Only well-known TLDs are matched, so Java packages (net.minecraft.server) are left alone
+static HOSTNAME_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?i)\b((?:[a-z0-9](?:[a-z0-9-]{0,61}[a-z0-9])?\.)+(?:com|net|org|io|me|gg|de|cz|sk|eu|uk|us|ru|pl|fr|nl|br|xyz|dev|co|host|club|online|fun|pro|info|biz|cloud|app|network|games|tk|ml))(?::(\d{1,5}))?\b",
+    )
+    .unwrap_or_else(|e| panic!("Failed to create 'HOSTNAME_REGEX': {}", e))
+});
+
+static UUID_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12}")
+        .unwrap_or_else(|e| panic!("Failed to create 'UUID_REGEX': {}", e))
+});
+
+// Lines that reveal a player name, the name is always the first group
+static PLAYER_NAME_REGEXES: LazyLock<Vec<Regex>> = LazyLock::new(|| {
+    [
+        r"UUID of player (\w{3,16}) is",
+        r"(\w{3,16})\[/[^\]]*\] logged in with entity id",
+        r"(\w{3,16}) (?:joined|left) the game",
+        r"(\w{3,16}) lost connection:",
+        r"(\w{3,16}) issued server command:",
+        r"<(\w{3,16})> ",
+        r"GameProfile@[0-9a-f]+\[id=[^,]*,name=(\w{3,16}),",
+    ]
+    .iter()
+    .map(|pattern| {
+        Regex::new(pattern).unwrap_or_else(|e| {
+            panic!(
+                "Failed to create 'PLAYER_NAME_REGEXES' ({}): {}",
+                pattern, e
+            )
+        })
+    })
+    .collect()
+});
+
+// Words that may be a player name, looked up in the names learned so far
+static PLAYER_WORD_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\b\w{3,16}\b")
+        .unwrap_or_else(|e| panic!("Failed to create 'PLAYER_WORD_REGEX': {}", e))
+});
+
+// Valid player names that are also words of the log itself, only replaced where the line
+// shows they're a player (chat, joins, ...), see `Redactor::learn_players`
+const COMMON_WORDS: &[&str] = &[
+    "trace",
+    "debug",
+    "info",
+    "warn",
+    "warning",
+    "error",
+    "severe",
+    "fatal",
+    "config",
+    "fine",
+    "finer",
+    "finest",
+    "server",
+    "main",
+    "console",
+    "rcon",
+    "player",
+    "players",
+    "steve",
+    "alex",
+    "thread",
+    "worker",
+    "minecraft",
+    "paper",
+    "spigot",
+    "bukkit",
+    "forge",
+    "fabric",
+    "velocity",
+    "done",
+    "true",
+    "false",
+    "null",
+    "world",
+    "nether",
+    "the_end",
+    "entity",
+    "chunk",
+];
+
+/// What gets redacted from a log before it's shown publicly.
+///
+/// Can be loaded from TOML, missing keys fall back to [`RedactionPolicy::default`]:
+///
+/// ```toml
+/// hostnames = true
+/// allow = ['^10\.']
+/// deny = ['secret-\w+']
+/// ```
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct RedactionPolicy {
+    pub ipv4: bool,
+    pub ipv6: bool,
+    pub hostnames: bool,
+    /// Names of players seen joining, chatting, running commands, ...
+    pub players: bool,
+    pub uuids: bool,
+    /// Replace values with stable per-log tokens (`{ip#3}`, `{player#12}`) instead of a
+    /// flat placeholder (`{ipv4}`), see [`Pseudonyms`]
+    pub pseudonymize: bool,
+    /// Mask webhooks, database credentials, API keys, ..., see [`SecretScrubber`]
+    pub secrets: bool,
+    /// Replace the port of `ip:port` pairs too
+    pub ports: bool,
+    /// Keep loopback and unspecified addresses (127.0.0.1, 0.0.0.0, ::1, ::)
+    pub keep_loopback: bool,
+    /// Keep plugin versions that look like IPv4 addresses (e.g. `SetSpawn v4.8.1.2`)
+    pub keep_plugin_versions: bool,
+    /// Lines containing any of these are left untouched...
+    pub keep_lines: Vec<String>,
+    /// ...unless they also contain any of these
+    pub redact_lines: Vec<String>,
+    /// Hostnames ending with any of these are kept
+    pub keep_hosts: Vec<String>,
+    /// Regexes, matching addresses and hostnames are kept
+    pub allow: Vec<String>,
+    /// Regexes, matches are always replaced with `{redacted}`
+    pub deny: Vec<String>,
+}
+
+impl Default for RedactionPolicy {
+    fn default() -> Self {
+        let keep_lines = [
+            "plugins/",
+            "Forge Mod Loader version",
+            "MinecraftForge v",
+            "openjdk",
+            "OpenJDK",
+        ];
+
+        let redact_lines = [
+            "at (", // Shotyr[/{ipv4}:58381] logged in with entity id 675
+            // at ([world]-102.50147912322777, 94.88908505183846, -117.07016565695118)
+            "logged in with entity id",
+        ];
+
+        let keep_hosts = [
+            "mojang.com",
+            "minecraft.net",
+            "minecraftservices.com",
+            "papermc.io",
+            "purpurmc.org",
+            "spigotmc.org",
+            "bukkit.org",
+            "velocitypowered.com",
+            "fabricmc.net",
+            "minecraftforge.net",
+            "neoforged.net",
+            "modrinth.com",
+            "curseforge.com",
+            "github.com",
+            "bstats.org",
+            "maven.org",
+            "apache.org",
+            "oracle.com",
+        ];
+
+        Self {
+            ipv4: true,
+            ipv6: true,
+            hostnames: false,
+            players: false,
+            uuids: false,
+            pseudonymize: false,
+            secrets: true,
+            ports: false,
+            keep_loopback: true,
+            keep_plugin_versions: true,
+            keep_lines: keep_lines.iter().map(|s| s.to_string()).collect(),
+            redact_lines: redact_lines.iter().map(|s| s.to_string()).collect(),
+            keep_hosts: keep_hosts.iter().map(|s| s.to_string()).collect(),
+            allow: Vec::new(),
+            deny: Vec::new(),
+        }
+    }
+}
+
+impl RedactionPolicy {
+    pub fn from_toml(content: &str) -> Result<Self, toml::de::Error> {
+        toml::from_str(content)
+    }
+
+    pub fn redactor(&self) -> Result<Redactor, regex::Error> {
+        let allow = compile_all(&self.allow)?;
+        let deny = compile_all(&self.deny)?;
+
+        Ok(Redactor {
+            policy: self.clone(),
+            allow,
+            deny,
+            plugin_versions: Vec::new(),
+            players: HashSet::new(),
+            pseudonyms: Pseudonyms::default(),
+            secrets: SecretScrubber::default(),
+        })
+    }
+}
+
+fn compile_all(patterns: &[String]) -> Result<Vec<Regex>, regex::Error> {
+    patterns.iter().map(|pattern| Regex::new(pattern)).collect()
+}
+
+/// Applies a [`RedactionPolicy`] to the lines of a single log, in order.
+///
+/// Keeps track of plugin versions, player names and pseudonyms seen so far, so create a new
+/// one for every log.
+pub struct Redactor {
+    policy: RedactionPolicy,
+    allow: Vec<Regex>,
+    deny: Vec<Regex>,
+    plugin_versions: Vec<String>,
+    /// Names of players seen so far, except common words
+    players: HashSet<String>,
+    pseudonyms: Pseudonyms,
+    secrets: SecretScrubber,
+}
+
+impl Default for Redactor {
+    fn default() -> Self {
+        RedactionPolicy::default()
+            .redactor()
+            .unwrap_or_else(|e| panic!("Failed to create default 'Redactor': {}", e))
+    }
+}
+
+impl Redactor {
+    /// Tokens handed out so far, empty unless [`RedactionPolicy::pseudonymize`] is set.
+    pub fn pseudonyms(&self) -> &Pseudonyms {
+        &self.pseudonyms
+    }
+
+    pub fn into_pseudonyms(self) -> Pseudonyms {
+        self.pseudonyms
+    }
+
+    /// Secrets masked so far, empty unless [`RedactionPolicy::secrets`] is set.
+    pub fn secret_report(&self) -> &SecretReport {
+        self.secrets.report()
+    }
+
+    pub fn redact(&mut self, line: String) -> String {
+        // Before anything else, the scrubber has to see every line to count them
+        let line = match self.policy.secrets {
+            true => self.secrets.scrub(line),
+            false => line,
+        };
+
+        if let Some(plugin) = StaticAnalyzer::plugin_bukkit(&line) {
+            self.plugin_versions.push(plugin.version);
+            return line;
+        }
+
+        if self
+            .policy
+            .keep_lines
+            .iter()
+            .any(|keep| line.contains(keep))
+            && !self.policy.redact_lines.iter().any(|r| line.contains(r))
+        {
+            return line;
+        }
+
+        let mut line = line;
+
+        for deny in &self.deny {
+            line = deny.replace_all(&line, "{redacted}").into_owned();
+        }
+
+        // Names are learned as players show up, earlier lines aren't revisited
+        if self.policy.players {
+            line = self.learn_players(&line);
+
+            if !self.players.is_empty() {
+                line = replace_standalone(&line, &PLAYER_WORD_REGEX, |captures| {
+                    let word = captures.get(0)?.as_str();
+
+                    match self.players.contains(word) {
+                        true => self.player(word),
+                        false => None,
+                    }
+                });
+            }
+        }
+
+        if self.policy.uuids {
+            line = replace_standalone(&line, &UUID_REGEX, |captures| self.uuid(captures));
+        }
+
+        // IPv6 first, so IPv4-mapped addresses (::ffff:1.2.3.4) are replaced as a whole
+        if self.policy.ipv6 {
+            line = replace_standalone(&line, &IPV6_REGEX, |captures| self.ipv6(captures));
+        }
+
+        if self.policy.ipv4 {
+            line = replace_standalone(&line, &IPV4_REGEX, |captures| self.ipv4(captures));
+        }
+
+        if self.policy.hostnames {
+            line = replace_standalone(&line, &HOSTNAME_REGEX, |captures| self.hostname(captures));
+        }
+
+        line
+    }
+
+    fn ipv4(&mut self, captures: &Captures) -> Option<String> {
+        let address = captures.get(1)?.as_str();
+        let ip = address.parse::<Ipv4Addr>().ok()?;
+
+        if self.is_allowed(address)
+            || (self.policy.keep_loopback && (ip.is_loopback() || ip.is_unspecified()))
+            || (self.policy.keep_plugin_versions
+                && self.plugin_versions.iter().any(|ver| ver.contains(address)))
+        {
+            return None;
+        }
+
+        let placeholder = self.placeholder(PseudonymKind::Ip, &ip.to_string(), address, "{ipv4}");
+        Some(self.with_port(&placeholder, captures.get(2).map(|m| m.as_str())))
+    }
+
+    fn ipv6(&mut self, captures: &Captures) -> Option<String> {
+        let address = captures.get(2)?.as_str();
+        let without_zone = address.split('%').next().unwrap_or(address);
+        let ip = without_zone.parse::<Ipv6Addr>().ok()?;
+
+        if self.is_allowed(address)
+            || (self.policy.keep_loopback && (ip.is_loopback() || ip.is_unspecified()))
+        {
+            return None;
+        }
+
+        let open = captures.get(1).map_or("", |m| m.as_str());
+        let close = captures.get(3).map_or("", |m| m.as_str());
+
+        // The port group only matches after a closing bracket ([::1]:25565)
+        let placeholder = self.placeholder(PseudonymKind::Ip, &ip.to_string(), address, "{ipv6}");
+        let placeholder = format!("{}{}{}", open, placeholder, close);
+        Some(self.with_port(&placeholder, captures.get(4).map(|m| m.as_str())))
+    }
+
+    fn hostname(&mut self, captures: &Captures) -> Option<String> {
+        let hostname = captures.get(1)?.as_str();
+        let lowercase = hostname.to_lowercase();
+
+        if self.is_allowed(hostname)
+            || self
+                .policy
+                .keep_hosts
+                .iter()
+                .any(|host| lowercase == *host || lowercase.ends_with(&format!(".{}", host)))
+        {
+            return None;
+        }
+
+        let placeholder = self.placeholder(PseudonymKind::Host, &lowercase, hostname, "{hostname}");
+        Some(self.with_port(&placeholder, captures.get(2).map(|m| m.as_str())))
+    }
+
+    fn player(&mut self, name: &str) -> Option<String> {
+        if self.is_allowed(name) {
+            return None;
+        }
+
+        Some(self.placeholder(
+            PseudonymKind::Player,
+            &name.to_lowercase(),
+            name,
+            "{player}",
+        ))
+    }
+
+    fn uuid(&mut self, captures: &Captures) -> Option<String> {
+        let uuid = captures.get(0)?.as_str();
+
+        if self.is_allowed(uuid) {
+            return None;
+        }
+
+        Some(self.placeholder(PseudonymKind::Uuid, &uuid.to_lowercase(), uuid, "{uuid}"))
+    }
+
+    /// Replaces the names in lines that show a player (chat, joins, commands, ...), and
+    /// remembers them to replace them anywhere else.
+    ///
+    /// Elsewhere names are matched case-sensitively, as the server prints them, and not at all
+    /// when they're also a common word of logs, so a player called `Server` or `ERROR` doesn't
+    /// get every `[Server thread/ERROR]` redacted.
+    fn learn_players(&mut self, line: &str) -> String {
+        let mut line = line.to_string();
+
+        for regex in PLAYER_NAME_REGEXES.iter() {
+            let mut output = String::with_capacity(line.len());
+            let mut last_end = 0;
+
+            for captures in regex.captures_iter(&line) {
+                let Some(name) = captures.get(1) else {
+                    continue;
+                };
+
+                if !COMMON_WORDS.contains(&name.as_str().to_lowercase().as_str()) {
+                    self.players.insert(name.as_str().to_string());
+                }
+
+                if let Some(replaced) = self.player(name.as_str()) {
+                    output.push_str(&line[last_end..name.start()]);
+                    output.push_str(&replaced);
+                    last_end = name.end();
+                }
+            }
+
+            output.push_str(&line[last_end..]);
+            line = output;
+        }
+
+        line
+    }
+
+    fn placeholder(
+        &mut self,
+        kind: PseudonymKind,
+        key: &str,
+        original: &str,
+        placeholder: &str,
+    ) -> String {
+        match self.policy.pseudonymize {
+            true => self.pseudonyms.token(kind, key, original),
+            false => placeholder.to_string(),
+        }
+    }
+
+    fn is_allowed(&self, value: &str) -> bool {
+        self.allow.iter().any(|allow| allow.is_match(value))
+    }
+
+    fn with_port(&self, placeholder: &str, port: Option<&str>) -> String {
+        match port {
+            None => placeholder.to_string(),
+            Some(_) if self.policy.ports => format!("{}:{{port}}", placeholder),
+            Some(port) => format!("{}:{}", placeholder, port),
+        }
+    }
+}
+
+/// Replaces matches that aren't part of a longer token (version strings, Java packages, ...).
+///
+/// `replacement` returns `None` to keep the match.
+fn replace_standalone(
+    line: &str,
+    regex: &Regex,
+    mut replacement: impl FnMut(&Captures) -> Option<String>,
+) -> String {
+    let mut output = String::with_capacity(line.len());
+    let mut last_end = 0;
+
+    for captures in regex.captures_iter(line) {
+        let Some(whole) = captures.get(0) else {
+            continue;
+        };
+
+        if !is_standalone(line, whole.start(), whole.end()) {
+            continue;
+        }
+
+        if let Some(replaced) = replacement(&captures) {
+            output.push_str(&line[last_end..whole.start()]);
+            output.push_str(&replaced);
+            last_end = whole.end();
+        }
+    }
+
+    output.push_str(&line[last_end..]);
+    output
+}
+
+fn is_standalone(line: &str, start: usize, end: usize) -> bool {
+    let is_word = |c: char| c.is_ascii_alphanumeric() || c == '_' || c == '-';
+
+    let before = line[..start].chars().next_back();
+    let mut after = line[end..].chars();
+
+    if before.is_some_and(|c| is_word(c) || c == '.') {
+        return false;
+    }
+
+    match after.next() {
+        Some(c) if is_word(c) => false,
+        // 1.2.3.4.5, but not the end of a sentence
+        Some('.') => !after.next().is_some_and(|c| c.is_ascii_alphanumeric()),
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn redact(lines: &[&str]) -> Vec<String> {
+        let mut redactor = RedactionPolicy {
+            players: true,
+            ..RedactionPolicy::default()
+        }
+        .redactor()
+        .unwrap();
+
+        lines
+            .iter()
+            .map(|line| redactor.redact(line.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn players() {
+        let lines = redact(&[
+            "[12:00:00] [User Authenticator #1/INFO]: UUID of player Notch is 069a79f4-44e9-4726-a5be-fca90e38aaf5",
+            "[12:00:01] [Server thread/INFO]: Notch joined the game",
+            "[12:00:02] [Server thread/INFO]: Notch has made the advancement [Stone Age]",
+            "[12:00:03] [Server thread/INFO]: <notch> lowercase is another word",
+        ]);
+
+        assert_eq!(
+            lines[0],
+            "[12:00:00] [User Authenticator #1/INFO]: UUID of player {player} is 069a79f4-44e9-4726-a5be-fca90e38aaf5"
+        );
+        assert_eq!(
+            lines[1],
+            "[12:00:01] [Server thread/INFO]: {player} joined the game"
+        );
+        assert_eq!(
+            lines[2],
+            "[12:00:02] [Server thread/INFO]: {player} has made the advancement [Stone Age]"
+        );
+        assert_eq!(
+            lines[3],
+            "[12:00:03] [Server thread/INFO]: <{player}> lowercase is another word"
+        );
+    }
+
+    #[test]
+    fn players_named_like_log_words() {
+        let lines = redact(&[
+            "[12:00:00] [Server thread/INFO]: Server joined the game",
+            "[12:00:01] [Server thread/INFO]: <ERROR> hi",
+            "[12:00:02] [Server thread/ERROR]: Encountered an unexpected exception",
+            "[12:00:03] [main/INFO]: Server is starting",
+        ]);
+
+        assert_eq!(
+            lines[0],
+            "[12:00:00] [Server thread/INFO]: {player} joined the game"
+        );
+        assert_eq!(lines[1], "[12:00:01] [Server thread/INFO]: <{player}> hi");
+        assert_eq!(
+            lines[2],
+            "[12:00:02] [Server thread/ERROR]: Encountered an unexpected exception"
+        );
+        assert_eq!(lines[3], "[12:00:03] [main/INFO]: Server is starting");
+    }
+
+    #[test]
+    fn many_players() {
+        let mut lines: Vec<String> = (0..5000)
+            .map(|idx| format!("[12:00:00] [Server thread/INFO]: Player{idx} joined the game"))
+            .collect();
+        lines.push(
+            "[12:30:00] [Server thread/INFO]: Player4321 has made the advancement [Stone Age]"
+                .to_string(),
+        );
+        lines.push(
+            "[12:30:01] [Server thread/INFO]: Player5000 and Player12345 are not players"
+                .to_string(),
+        );
+
+        let lines: Vec<&str> = lines.iter().map(String::as_str).collect();
+        let lines = redact(&lines);
+
+        assert_eq!(
+            lines[5000],
+            "[12:30:00] [Server thread/INFO]: {player} has made the advancement [Stone Age]"
+        );
+        assert_eq!(
+            lines[5001],
+            "[12:30:01] [Server thread/INFO]: Player5000 and Player12345 are not players"
+        );
+    }
+}
@@ -0,0 +1,157 @@
+use super::{reader::LogReader, Log};
+use crate::{
+    parser::{log_level::CustomLevel, stream::ParserStream},
+    Result,
+};
+use chrono::NaiveDate;
+use regex::Regex;
+use std::{
+    path::{Path, PathBuf},
+    sync::{Arc, LazyLock},
+};
+
+// 2024-01-12-3.log.gz
+static ROTATED_LOG_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(\d{4}-\d{2}-\d{2})-(\d+)\.log(\.gz)?$").unwrap_or_else(|e| {
+        panic!("Failed to create 'ROTATED_LOG_REGEX': {}", e);
+    })
+});
+
+const LATEST_LOG: &str = "latest.log";
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum LogFileKind {
+    Rotated { date: NaiveDate, index: u32 },
+    Other,
+    Latest,
+}
+
+impl LogFileKind {
+    pub fn from_path(path: &Path) -> Self {
+        let file_name = match path.file_name().and_then(|name| name.to_str()) {
+            Some(name) => name,
+            None => return LogFileKind::Other,
+        };
+
+        if file_name == LATEST_LOG {
+            return LogFileKind::Latest;
+        }
+
+        let rotated = ROTATED_LOG_REGEX.captures(file_name).and_then(|captures| {
+            let date = NaiveDate::parse_from_str(captures.get(1)?.as_str(), "%Y-%m-%d").ok()?;
+            let index = captures.get(2)?.as_str().parse::<u32>().ok()?;
+            Some(LogFileKind::Rotated { date, index })
+        });
+
+        rotated.unwrap_or(LogFileKind::Other)
+    }
+
+    /// Date of the log's lines, known only for rotated files.
+    pub fn date(&self) -> Option<NaiveDate> {
+        match self {
+            LogFileKind::Rotated { date, .. } => Some(*date),
+            LogFileKind::Other | LogFileKind::Latest => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct LogFile {
+    pub path: Arc<Path>,
+    pub kind: LogFileKind,
+}
+
+impl LogFile {
+    /// Parses the file into entries, dating time-only timestamps with the rotated file's date.
+    pub async fn entries(
+        &self,
+        custom_levels: Vec<CustomLevel>,
+    ) -> Result<ParserStream<Log<LogReader>>> {
+        let entries = Log::open(&self.path).await?.entries(custom_levels);
+
+        Ok(match self.kind.date() {
+            Some(date) => entries.with_date(date),
+            None => entries,
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SourcedLine {
+    pub source: Arc<Path>,
+    pub line: String,
+}
+
+/// Several log files read as one continuous timeline.
+///
+/// Files are ordered oldest first: rotated `YYYY-MM-DD-N.log(.gz)` files by date and index,
+/// then any other files in the order they were given, and `latest.log` last.
+pub struct LogSet {
+    files: Vec<LogFile>,
+}
+
+impl LogSet {
+    pub fn from_files<P: Into<PathBuf>>(paths: impl IntoIterator<Item = P>) -> Self {
+        let mut files: Vec<LogFile> = paths
+            .into_iter()
+            .map(|path| {
+                let path: PathBuf = path.into();
+                let kind = LogFileKind::from_path(&path);
+                LogFile {
+                    path: Arc::from(path),
+                    kind,
+                }
+            })
+            .collect();
+
+        // Stable sort, so unrecognized files keep their relative order
+        files.sort_by_key(|file| file.kind);
+
+        Self { files }
+    }
+
+    /// Collects `latest.log` and rotated logs from a `logs/` directory.
+    ///
+    /// Other files (`debug.log`, `debug-N.log.gz`, ...) are skipped because they duplicate
+    /// the lines of the regular logs.
+    pub async fn from_dir(path: impl AsRef<Path>) -> Result<Self> {
+        let mut paths = Vec::new();
+
+        let mut entries = tokio::fs::read_dir(path).await?;
+
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+
+            if !entry.file_type().await?.is_file() {
+                continue;
+            }
+
+            if LogFileKind::from_path(&path) != LogFileKind::Other {
+                paths.push(path);
+            }
+        }
+
+        Ok(Self::from_files(paths))
+    }
+
+    pub fn files(&self) -> &[LogFile] {
+        &self.files
+    }
+
+    pub async fn lines(&self) -> Result<Vec<SourcedLine>> {
+        let mut lines = Vec::new();
+
+        for file in &self.files {
+            let log = Log::open(&file.path).await?;
+
+            for line in log.lines().await? {
+                lines.push(SourcedLine {
+                    source: file.path.clone(),
+                    line,
+                });
+            }
+        }
+
+        Ok(lines)
+    }
+}
@@ -0,0 +1,346 @@
+use super::stacktrace::{StackFrame, StackTrace};
+use regex::Regex;
+use serde::Serialize;
+use std::sync::LazyLock;
+
+const CRASH_REPORT_HEADER: &str = "---- Minecraft Crash Report ----";
+const WALKTHROUGH_HEADER: &str = "A detailed walkthrough of the error";
+const SYSTEM_DETAILS: &str = "System Details";
+
+// -- Head --, -- Block entity being ticked --
+static SECTION_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^-- (.+) --$")
+        .unwrap_or_else(|e| panic!("Failed to create 'SECTION_REGEX': {}", e))
+});
+
+// Thread: Server thread, \tMinecraft Version: 1.20.1
+static DETAIL_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^([A-Za-z][^:\t]*?):(?: (.*))?$")
+        .unwrap_or_else(|e| panic!("Failed to create 'DETAIL_REGEX': {}", e))
+});
+
+// Forge 1.18+: Some Mod (somemod), Version: 1.0
+static SUSPECTED_MOD_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(.+?) \(([^()]+)\)(?:, Version: (.+))?$")
+        .unwrap_or_else(|e| panic!("Failed to create 'SUSPECTED_MOD_REGEX': {}", e))
+});
+
+// Fabric: fabric-api: Fabric API 0.92.0+1.20.1
+static FABRIC_MOD_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^([a-z0-9_.-]+): (.+) (\S+)$")
+        .unwrap_or_else(|e| panic!("Failed to create 'FABRIC_MOD_REGEX': {}", e))
+});
+
+/// A `key: value` line of a crash report section, values of lists like `Mod List` span
+/// several lines.
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CrashDetail {
+    pub key: String,
+    pub value: String,
+}
+
+/// A `-- Title --` section of the detailed walkthrough.
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CrashSection {
+    pub title: String,
+    pub details: Vec<CrashDetail>,
+    /// Frames under `Stacktrace:`, where the section's code was running
+    pub frames: Vec<StackFrame>,
+}
+
+impl CrashSection {
+    pub fn detail(&self, key: &str) -> Option<&str> {
+        self.details
+            .iter()
+            .find(|detail| detail.key == key)
+            .map(|detail| detail.value.as_str())
+    }
+}
+
+#[derive(Serialize, Clone, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CrashMod {
+    pub id: String,
+    pub name: Option<String>,
+    pub version: Option<String>,
+    /// Jar the mod was loaded from, when listed
+    pub file: Option<String>,
+}
+
+/// A Vanilla, Forge or Fabric crash report, as written to `crash-reports/crash-*.txt`.
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CrashReport {
+    /// As written, e.g. `2024-01-12 12:34:56` or `1/12/24 12:34 PM` in old versions
+    pub time: Option<String>,
+    /// e.g. `Exception in server tick loop`, `Ticking entity`
+    pub description: Option<String>,
+    pub exception: Option<StackTrace>,
+    pub sections: Vec<CrashSection>,
+    /// Mods Forge suspects of causing the crash
+    pub suspected_mods: Vec<CrashMod>,
+    pub mods: Vec<CrashMod>,
+    /// 1-based number of the report's first line in the log
+    pub start_line: usize,
+}
+
+impl CrashReport {
+    /// Parses the first crash report in the lines, either a crash report file or one printed
+    /// into a log.
+    pub fn find(lines: &[String]) -> Option<Self> {
+        let start = lines
+            .iter()
+            .position(|line| line.trim() == CRASH_REPORT_HEADER)?;
+
+        let mut report = Self::parse(&lines[start + 1..]);
+        report.start_line = start + 1;
+
+        Some(report)
+    }
+
+    fn parse(lines: &[String]) -> Self {
+        let walkthrough = lines
+            .iter()
+            .position(|line| line.starts_with(WALKTHROUGH_HEADER))
+            .unwrap_or(lines.len());
+
+        let (head, sections) = lines.split_at(walkthrough);
+
+        let mut time = None;
+        let mut description = None;
+        let mut exception_start = None;
+
+        for (idx, line) in head.iter().enumerate() {
+            if let Some(value) = line.strip_prefix("Time: ") {
+                time = Some(value.trim().to_string());
+            } else if let Some(value) = line.strip_prefix("Description: ") {
+                description = Some(value.trim().to_string());
+                exception_start = Some(idx + 1);
+                break;
+            }
+        }
+
+        let exception = exception_start.and_then(|start| StackTrace::parse(&head[start..]));
+
+        let mut report = Self {
+            time,
+            description,
+            exception,
+            sections: parse_sections(sections),
+            suspected_mods: Vec::new(),
+            mods: Vec::new(),
+            start_line: 1,
+        };
+
+        report.suspected_mods = report.find_suspected_mods();
+        report.mods = report.find_mods();
+
+        report
+    }
+
+    pub fn section(&self, title: &str) -> Option<&CrashSection> {
+        self.sections.iter().find(|section| section.title == title)
+    }
+
+    /// A value from `System Details`, e.g. `Minecraft Version` or `Java Version`.
+    pub fn system_detail(&self, key: &str) -> Option<&str> {
+        self.section(SYSTEM_DETAILS)?.detail(key)
+    }
+
+    pub fn minecraft_version(&self) -> Option<&str> {
+        self.system_detail("Minecraft Version")
+    }
+
+    /// e.g. `17.0.8`, without the vendor.
+    pub fn java_version(&self) -> Option<&str> {
+        let version = self.system_detail("Java Version")?;
+        version.split(',').next().map(str::trim)
+    }
+
+    /// The brand in `Is Modded`, e.g. `forge`, `fabric` or `Paper`.
+    pub fn brand(&self) -> Option<&str> {
+        let is_modded = self.system_detail("Is Modded")?;
+        let (_, brand) = is_modded.split_once("brand changed to '")?;
+        brand.split('\'').next()
+    }
+
+    fn find_suspected_mods(&self) -> Vec<CrashMod> {
+        let Some(suspected) = self.sections.iter().find_map(|section| {
+            section
+                .detail("Suspected Mods")
+                .or_else(|| section.detail("Suspected Mod"))
+        }) else {
+            return Vec::new();
+        };
+
+        suspected
+            .lines()
+            // Nested lines are the mod's issue tracker and frames
+            .filter(|line| !line.starts_with('\t'))
+            .filter_map(|line| SUSPECTED_MOD_REGEX.captures(line.trim()))
+            .map(|captures| CrashMod {
+                id: captures[2].to_string(),
+                name: Some(captures[1].to_string()),
+                version: captures.get(3).map(|m| m.as_str().to_string()),
+                file: None,
+            })
+            .collect()
+    }
+
+    fn find_mods(&self) -> Vec<CrashMod> {
+        let Some(system_details) = self.section(SYSTEM_DETAILS) else {
+            return Vec::new();
+        };
+
+        // Forge 1.13+
+        if let Some(mod_list) = system_details.detail("Mod List") {
+            return mod_list.lines().filter_map(forge_mod).collect();
+        }
+
+        if let Some(fabric_mods) = system_details.detail("Fabric Mods") {
+            return fabric_mods
+                .lines()
+                // Nested lines are the jars included in a mod
+                .filter(|line| !line.starts_with('\t'))
+                .filter_map(|line| FABRIC_MOD_REGEX.captures(line.trim()))
+                .map(|captures| CrashMod {
+                    id: captures[1].to_string(),
+                    name: Some(captures[2].to_string()),
+                    version: Some(captures[3].to_string()),
+                    file: None,
+                })
+                .collect();
+        }
+
+        // Forge 1.12 and older print a table after `States:`
+        system_details
+            .details
+            .iter()
+            .flat_map(|detail| detail.value.lines())
+            .filter_map(legacy_forge_mod)
+            .collect()
+    }
+}
+
+// forge-1.20.1-47.2.0-universal.jar |Forge |forge |47.2.0 |DONE |Manifest: NOSIGNATURE
+fn forge_mod(line: &str) -> Option<CrashMod> {
+    let columns: Vec<&str> = line.split('|').map(str::trim).collect();
+
+    match columns.as_slice() {
+        [file, name, id, version, ..] if !id.is_empty() => Some(CrashMod {
+            id: id.to_string(),
+            name: Some(name.to_string()),
+            version: Some(version.to_string()),
+            file: Some(file.to_string()),
+        }),
+        _ => None,
+    }
+}
+
+// | LCHIJA | minecraft | 1.12.2 | minecraft.jar | None |
+fn legacy_forge_mod(line: &str) -> Option<CrashMod> {
+    let columns: Vec<&str> = line
+        .trim()
+        .strip_prefix('|')?
+        .split('|')
+        .map(str::trim)
+        .collect();
+
+    match columns.as_slice() {
+        [state, id, version, source, ..]
+            if *state != "State" && !state.starts_with(':') && !id.is_empty() =>
+        {
+            Some(CrashMod {
+                id: id.to_string(),
+                name: None,
+                version: Some(version.to_string()),
+                file: Some(source.to_string()),
+            })
+        }
+        _ => None,
+    }
+}
+
+fn parse_sections(lines: &[String]) -> Vec<CrashSection> {
+    let mut sections: Vec<CrashSection> = Vec::new();
+
+    // Indentation of the section's `key: value` lines, one tab under `Details:`
+    let mut detail_depth = 0;
+    let mut in_stacktrace = false;
+
+    for line in lines {
+        if let Some(captures) = SECTION_REGEX.captures(line.trim_end()) {
+            sections.push(CrashSection {
+                title: captures[1].to_string(),
+                details: Vec::new(),
+                frames: Vec::new(),
+            });
+            detail_depth = 0;
+            in_stacktrace = false;
+            continue;
+        }
+
+        let Some(section) = sections.last_mut() else {
+            continue;
+        };
+
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        match line.trim_end() {
+            "Details:" => {
+                detail_depth = 1;
+                in_stacktrace = false;
+                continue;
+            }
+            "Stacktrace:" => {
+                in_stacktrace = true;
+                continue;
+            }
+            _ => {}
+        }
+
+        if in_stacktrace {
+            match StackFrame::parse(line) {
+                Some(frame) => {
+                    section.frames.push(frame);
+                    continue;
+                }
+                None => in_stacktrace = false,
+            }
+        }
+
+        let depth = line.chars().take_while(|&c| c == '\t').count();
+        let content = &line[depth..];
+
+        let detail = match depth <= detail_depth {
+            true => DETAIL_REGEX.captures(content.trim_end()),
+            false => None,
+        };
+
+        match (detail, section.details.last_mut()) {
+            (Some(captures), _) => section.details.push(CrashDetail {
+                key: captures[1].to_string(),
+                value: captures
+                    .get(2)
+                    .map(|m| m.as_str().trim().to_string())
+                    .unwrap_or_default(),
+            }),
+            // Continues the previous value, keeping its own nesting
+            (None, Some(detail)) => {
+                let nested = &line[depth.min(detail_depth + 1)..];
+
+                if !detail.value.is_empty() {
+                    detail.value.push('\n');
+                }
+                detail.value.push_str(nested.trim_end());
+            }
+            (None, None) => {}
+        }
+    }
+
+    sections
+}
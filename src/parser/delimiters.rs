@@ -1,37 +1,9 @@
-use regex::Regex;
-use std::sync::LazyLock;
+use super::parser::LogEntry;
+use serde::Serialize;
 
-// TODO - rewrite
-
-// WARN]:
-static BRACKET_COLON_REGEX: LazyLock<Regex> = LazyLock::new(|| {
-    Regex::new("((INFO|WARN|WARNING|ERROR|FATAL|SEVERE)]:)").unwrap_or_else(|err| {
-        panic!("Failed to create 'BRACKET_COLON_REGEX': {}", err);
-    })
-});
-
-// WARN]
-static BRACKET_REGEX: LazyLock<Regex> = LazyLock::new(|| {
-    Regex::new("((INFO|WARN|WARNING|ERROR|FATAL|SEVERE)])").unwrap_or_else(|err| {
-        panic!("Failed to create 'BRACKET_REGEX': {}", err);
-    })
-});
-
-// WARN:
-static COLON_REGEX: LazyLock<Regex> = LazyLock::new(|| {
-    Regex::new("((INFO|WARN|WARNING|ERROR|FATAL|SEVERE):)").unwrap_or_else(|err| {
-        panic!("Failed to create 'COLON_REGEX': {}", err);
-    })
-});
-
-// WARN
-static NOCOLON_NOBRACKET_REGEX: LazyLock<Regex> = LazyLock::new(|| {
-    Regex::new("(INFO|WARN|WARNING|ERROR|FATAL|SEVERE)").unwrap_or_else(|err| {
-        panic!("Failed to create 'NOCOLON_NOBRACKET_REGEX': {}", err);
-    })
-});
-
-#[derive(Clone, Copy)]
+/// How the level is closed in a line's header, e.g. `]:` in `[Server thread/INFO]:`.
+#[derive(Serialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
 pub enum DelimiterType {
     BracketColon,
     Bracket,
@@ -39,6 +11,14 @@ pub enum DelimiterType {
     NoColonNoBracket,
 }
 
+// JUL's FINER and FINEST are as verbose as TRACE, FINE and CONFIG as DEBUG
+pub(crate) const TRACE_TOKENS: &[&str] = &["TRACE", "FINER", "FINEST"];
+pub(crate) const DEBUG_TOKENS: &[&str] = &["DEBUG", "FINE", "CONFIG"];
+pub(crate) const INFO_TOKENS: &[&str] = &["INFO"];
+pub(crate) const WARN_TOKENS: &[&str] = &["WARN", "WARNING"];
+pub(crate) const ERROR_TOKENS: &[&str] = &["ERROR", "SEVERE"];
+pub(crate) const FATAL_TOKENS: &[&str] = &["FATAL"];
+
 impl std::fmt::Display for DelimiterType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -50,75 +30,37 @@ impl std::fmt::Display for DelimiterType {
     }
 }
 
-pub struct Delimiters {
-    pub info: Vec<String>,
-    pub warn: Vec<String>,
-    pub error: Vec<String>,
-    pub custom: Vec<String>,
+/// Consecutive entries written in the same format, e.g. vanilla `[INFO]:` lines followed by
+/// a wrapper's `INFO:` lines.
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FormatSegment {
+    pub delimiter_type: DelimiterType,
+    pub start_line: usize,
+    pub end_line: usize,
 }
 
-impl Delimiters {
-    pub fn new(custom_delimiters: Vec<String>, delimiter_type: DelimiterType) -> Self {
-        let info = ["INFO"];
-        let warn = ["WARN", "WARNING"];
-        let error = ["ERROR", "SEVERE", "FATAL"];
-        let custom = custom_delimiters;
-
-        let info = info
-            .iter()
-            .map(|del| format!("{del}{delimiter_type}"))
-            .collect();
-        let warn = warn
-            .iter()
-            .map(|del| format!("{del}{delimiter_type}"))
-            .collect();
-        let error = error
-            .iter()
-            .map(|del| format!("{del}{delimiter_type}"))
-            .collect();
-        let custom = custom
-            .iter()
-            .map(|del| format!("{del}{delimiter_type}"))
-            .collect();
-
-        Self {
-            info,
-            warn,
-            error,
-            custom,
+/// Splits parsed entries into [`FormatSegment`]s. Entries without a level don't start a
+/// segment of their own.
+pub fn format_segments(entries: &[LogEntry]) -> Vec<FormatSegment> {
+    let mut segments: Vec<FormatSegment> = Vec::new();
+
+    for entry in entries {
+        let Some(delimiter_type) = entry.format else {
+            continue;
+        };
+
+        match segments.last_mut() {
+            Some(segment) if segment.delimiter_type == delimiter_type => {
+                segment.end_line = entry.end_line;
+            }
+            _ => segments.push(FormatSegment {
+                delimiter_type,
+                start_line: entry.start_line,
+                end_line: entry.end_line,
+            }),
         }
     }
-}
-
-const DELIMITER_MAX_MATCHES: usize = 125;
-
-fn count_matches(lines: &[String], regex: Regex) -> usize {
-    lines
-        .iter()
-        .take(DELIMITER_MAX_MATCHES)
-        .filter(|line| regex.is_match(line))
-        .count()
-}
-
-pub fn detect_delimiter_type(lines: &[String]) -> DelimiterType {
-    let delimiter_regexes: Vec<(&'static Regex, DelimiterType)> = vec![
-        (&NOCOLON_NOBRACKET_REGEX, DelimiterType::NoColonNoBracket),
-        (&COLON_REGEX, DelimiterType::Colon),
-        (&BRACKET_REGEX, DelimiterType::Bracket),
-        (&BRACKET_COLON_REGEX, DelimiterType::BracketColon),
-    ];
-
-    #[allow(clippy::needless_borrowed_reference)]
-    let delimiter_type = delimiter_regexes
-        .iter()
-        .map(|(&ref regex, delimiter_type)| {
-            let count = count_matches(lines, regex.clone());
-            (count, delimiter_type)
-        })
-        .max_by_key(|&(count, _)| count)
-        .unwrap_or((0, &DelimiterType::NoColonNoBracket))
-        .1
-        .to_owned();
 
-    delimiter_type
+    segments
 }
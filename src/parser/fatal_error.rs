@@ -0,0 +1,517 @@
+use regex::Regex;
+use serde::Serialize;
+use std::sync::LazyLock;
+
+/// Frames kept of the native and Java stacks.
+const MAX_FRAMES: usize = 10;
+
+const FATAL_ERROR_HEADER: &str =
+    "# A fatal error has been detected by the Java Runtime Environment";
+const OUT_OF_MEMORY_HEADER: &str =
+    "# There is insufficient memory for the Java Runtime Environment to continue";
+
+// Libraries of graphics drivers: NVIDIA, AMD, Intel and Mesa on Windows and Linux
+const GPU_LIBRARIES: &[&str] = &[
+    "nvoglv",
+    "nvd3dum",
+    "nvwgf2um",
+    "libnvidia",
+    "atio6axx",
+    "atig6pxx",
+    "atioglxx",
+    "amdxc",
+    "ig4icd",
+    "ig7icd",
+    "ig75icd",
+    "ig8icd",
+    "ig9icd",
+    "ig11icd",
+    "ig12icd",
+    "igxelp",
+    "_dri.so",
+    "libgl.so",
+    "libglx",
+    "opengl32",
+    "vulkan-1",
+    "libvulkan",
+];
+
+// The JVM itself and the OS libraries everything calls into, not the culprit on their own.
+// With the `.` so `libnet.` doesn't match `libnetty_transport_native_epoll`.
+const JVM_LIBRARIES: &[&str] = &[
+    "libjvm",
+    "jvm.dll",
+    "libjava.",
+    "java.dll",
+    "libjli.",
+    "jli.dll",
+    "libjimage.",
+    "jimage.dll",
+    "libzip.",
+    "zip.dll",
+    "libnio.",
+    "nio.dll",
+    "libnet.",
+    "net.dll",
+    "libverify.",
+    "verify.dll",
+    "libmanagement.",
+    "management.dll",
+    "libawt",
+    "awt.dll",
+    "libfontmanager",
+    "fontmanager.dll",
+    "libjsvml",
+    "jsvml.dll",
+    "libjsig.",
+];
+// The `java` launcher, e.g. `C  [java+0xb8f]`
+const JVM_LAUNCHERS: &[&str] = &["java", "java.exe", "javaw.exe"];
+const SYSTEM_LIBRARIES: &[&str] = &[
+    "libc.",
+    "libc-",
+    "ld-musl",
+    "libpthread",
+    "ld-linux",
+    "libm.",
+    "libm-",
+    "libdl.",
+    "librt.",
+    "libstdc++",
+    "libgcc_s",
+    "libz.",
+    "ntdll.dll",
+    "kernel32.dll",
+    "kernelbase.dll",
+    "ucrtbase.dll",
+    "msvcrt.dll",
+    "msvcp140.dll",
+    "vcruntime140",
+    "libsystem_",
+    "libdyld",
+];
+
+// #  SIGSEGV (0xb) at pc=0x00007f8b2c1d2e5a, pid=12345, tid=12346
+// #  EXCEPTION_ACCESS_VIOLATION (0xc0000005) at pc=0x00007ffc2d4b6e5a, pid=1234, tid=5678
+static SIGNAL_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^#\s+(\w+ \(0x[0-9a-fA-F]+\)) at pc=(0x[0-9a-fA-F]+), pid=(\d+)")
+        .unwrap_or_else(|e| panic!("Failed to create 'SIGNAL_REGEX': {}", e))
+});
+
+// C  [libc.so.6+0x9a8fe]  __memmove_avx_unaligned_erms+0x2e
+// J 1234 c2 net.minecraft.Foo.bar(I)V (42 bytes) @ 0x00007f8b2c1d2e5a [0x...+0x...]
+static FRAME_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^([CVvjJA])\s+(?:\[([^\]+]+)(?:\+0x[0-9a-fA-F]+)?\]\s*)?(.*)$")
+        .unwrap_or_else(|e| panic!("Failed to create 'FRAME_REGEX': {}", e))
+});
+
+// Current thread (0x00007f8b2c1d2e5a):  JavaThread "Server thread" [_thread_in_native, ...]
+static CURRENT_THREAD_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"^Current thread \([^)]*\):\s+(\w+)(?: "([^"]*)")?"#)
+        .unwrap_or_else(|e| panic!("Failed to create 'CURRENT_THREAD_REGEX': {}", e))
+});
+
+// (build 17.0.8+7), (build 1.8.0_381-b09)
+static JAVA_VERSION_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\(build ([^)+-]+)")
+        .unwrap_or_else(|e| panic!("Failed to create 'JAVA_VERSION_REGEX': {}", e))
+});
+
+// -Xmx4G, -Xms512m
+static HEAP_FLAG_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"-Xm([xs])(\d+)([kKmMgGtT]?)\b")
+        .unwrap_or_else(|e| panic!("Failed to create 'HEAP_FLAG_REGEX': {}", e))
+});
+
+// Heap address: 0x0000000700000000, size: 4096 MB, Compressed Oops mode: Zero based
+static HEAP_ADDRESS_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^Heap address: \S+ size: (\d+) MB")
+        .unwrap_or_else(|e| panic!("Failed to create 'HEAP_ADDRESS_REGEX': {}", e))
+});
+
+// Memory: 4k page, physical 16384000k(1024000k free), swap 0k(0k free)
+static PHYSICAL_MEMORY_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^Memory: \d+k page, physical (\d+)k")
+        .unwrap_or_else(|e| panic!("Failed to create 'PHYSICAL_MEMORY_REGEX': {}", e))
+});
+
+// memory_limit_in_bytes: 4294967296, or 4194304 k since JDK 19
+static CONTAINER_LIMIT_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^memory_limit_in_bytes: (\d+)(?: ?([kKmMgG]))?")
+        .unwrap_or_else(|e| panic!("Failed to create 'CONTAINER_LIMIT_REGEX': {}", e))
+});
+
+#[derive(Serialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub enum FatalErrorCause {
+    /// The OS refused the JVM more memory, too little RAM or swap for the heap plus the JVM's
+    /// own memory
+    OutOfNativeMemory,
+    /// Like [`FatalErrorCause::OutOfNativeMemory`], but inside a container's memory limit,
+    /// usually one set too close to `-Xmx`
+    ContainerOutOfMemory,
+    /// Crashed in a graphics driver, e.g. from a client mod or an outdated driver
+    GpuDriver,
+    /// Crashed in a native library loaded by the server, a plugin or a mod
+    NativeLibrary,
+}
+
+impl FatalErrorCause {
+    pub fn name(&self) -> &'static str {
+        match self {
+            FatalErrorCause::OutOfNativeMemory => "out_of_native_memory",
+            FatalErrorCause::ContainerOutOfMemory => "container_out_of_memory",
+            FatalErrorCause::GpuDriver => "gpu_driver",
+            FatalErrorCause::NativeLibrary => "native_library",
+        }
+    }
+}
+
+/// A frame of the native or Java stack.
+#[derive(Serialize, Clone, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct NativeFrame {
+    /// `C` native code, `V` the JVM, `j` interpreted, `J` compiled and `v` generated Java code
+    pub kind: char,
+    /// e.g. `libc.so.6` or `nvoglv64.dll`
+    pub library: Option<String>,
+    /// Function or Java method, e.g. `net.minecraft.Foo.bar(I)V`
+    pub symbol: String,
+}
+
+impl NativeFrame {
+    fn parse(line: &str) -> Option<Self> {
+        let captures = FRAME_REGEX.captures(line.trim())?;
+        let kind = captures[1].chars().next()?;
+        let rest = captures.get(3).map_or("", |m| m.as_str()).trim();
+
+        let symbol = match kind {
+            // J 1234 c2 net.minecraft.Foo.bar(I)V (42 bytes) @ ...
+            'J' => rest
+                .split_whitespace()
+                .find(|part| part.contains('('))
+                .unwrap_or(rest),
+            _ => rest,
+        };
+
+        Some(Self {
+            kind,
+            library: captures.get(2).map(|m| m.as_str().to_string()),
+            symbol: symbol.to_string(),
+        })
+    }
+}
+
+/// Heap sizes in bytes, from the JVM flags.
+#[derive(Serialize, Clone, PartialEq, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct HeapConfiguration {
+    /// `-Xmx`, or the reserved heap size without it
+    pub max: Option<u64>,
+    /// `-Xms`
+    pub initial: Option<u64>,
+}
+
+/// A JVM fatal error log, the `hs_err_pid*.log` written when the JVM crashes in native code
+/// or runs out of native memory.
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FatalErrorLog {
+    /// e.g. `SIGSEGV (0xb)` or `EXCEPTION_ACCESS_VIOLATION (0xc0000005)`
+    pub signal: Option<String>,
+    pub pc: Option<String>,
+    pub pid: Option<u32>,
+    /// The failed allocation, when the JVM ran out of native memory
+    pub out_of_memory: Option<String>,
+    /// e.g. `OpenJDK Runtime Environment Temurin-17.0.8+7 (17.0.8+7) (build 17.0.8+7)`
+    pub jre_version: Option<String>,
+    pub java_vm: Option<String>,
+    pub problematic_frame: Option<NativeFrame>,
+    pub current_thread: Option<String>,
+    pub command_line: Option<String>,
+    pub heap: HeapConfiguration,
+    /// Physical memory of the machine in bytes
+    pub physical_memory: Option<u64>,
+    /// Memory limit of the container in bytes, `None` when unlimited or not in a container
+    pub container_memory_limit: Option<u64>,
+    /// Top of the native stack
+    pub native_frames: Vec<NativeFrame>,
+    /// Top of the Java stack
+    pub java_frames: Vec<NativeFrame>,
+    pub cause: Option<FatalErrorCause>,
+}
+
+enum Block {
+    None,
+    ProblematicFrame,
+    NativeFrames,
+    JavaFrames,
+}
+
+impl FatalErrorLog {
+    /// Parses the lines when they're a fatal error log.
+    pub fn find(lines: &[String]) -> Option<Self> {
+        let is_fatal_error = lines.iter().take(10).any(|line| {
+            line.starts_with(FATAL_ERROR_HEADER) || line.starts_with(OUT_OF_MEMORY_HEADER)
+        });
+
+        match is_fatal_error {
+            true => Some(Self::parse(lines)),
+            false => None,
+        }
+    }
+
+    fn parse(lines: &[String]) -> Self {
+        let mut log = Self {
+            signal: None,
+            pc: None,
+            pid: None,
+            out_of_memory: None,
+            jre_version: None,
+            java_vm: None,
+            problematic_frame: None,
+            current_thread: None,
+            command_line: None,
+            heap: HeapConfiguration::default(),
+            physical_memory: None,
+            container_memory_limit: None,
+            native_frames: Vec::new(),
+            java_frames: Vec::new(),
+            cause: None,
+        };
+
+        let mut block = Block::None;
+        let mut reserved_heap = None;
+
+        for line in lines {
+            let line = line.trim_end();
+
+            match block {
+                Block::ProblematicFrame => {
+                    log.problematic_frame = line
+                        .strip_prefix('#')
+                        .and_then(|frame| NativeFrame::parse(frame.trim()));
+                    block = Block::None;
+                    continue;
+                }
+                Block::NativeFrames | Block::JavaFrames if line.is_empty() => {
+                    block = Block::None;
+                    continue;
+                }
+                Block::NativeFrames | Block::JavaFrames => {
+                    let frames = match block {
+                        Block::NativeFrames => &mut log.native_frames,
+                        _ => &mut log.java_frames,
+                    };
+
+                    if frames.len() < MAX_FRAMES {
+                        frames.extend(NativeFrame::parse(line));
+                    }
+                    continue;
+                }
+                Block::None => {}
+            }
+
+            if let Some(captures) = SIGNAL_REGEX.captures(line) {
+                log.signal = Some(captures[1].to_string());
+                log.pc = Some(captures[2].to_string());
+                log.pid = captures[3].parse().ok();
+            } else if line.starts_with("# Native memory allocation")
+                || line.starts_with("# Out of Memory Error")
+            {
+                log.out_of_memory
+                    .get_or_insert_with(|| line.trim_start_matches('#').trim().to_string());
+            } else if let Some(version) = line.strip_prefix("# JRE version: ") {
+                log.jre_version = Some(version.trim().to_string());
+            } else if let Some(vm) = line.strip_prefix("# Java VM: ") {
+                log.java_vm = Some(vm.trim().to_string());
+            } else if line.starts_with("# Problematic frame:") {
+                block = Block::ProblematicFrame;
+            } else if line.starts_with("Native frames:") {
+                block = Block::NativeFrames;
+            } else if line.starts_with("Java frames:") {
+                block = Block::JavaFrames;
+            } else if let Some(captures) = CURRENT_THREAD_REGEX.captures(line) {
+                // Threads of the JVM (VMThread, GCTaskThread) have no quoted name
+                let name = captures.get(2).unwrap_or_else(|| captures.get(1).unwrap());
+                log.current_thread = Some(name.as_str().to_string());
+            } else if let Some(command_line) = line.strip_prefix("Command Line: ") {
+                log.command_line = Some(command_line.trim().to_string());
+            } else if let Some(jvm_args) = line.strip_prefix("jvm_args: ") {
+                log.heap_flags(jvm_args);
+            } else if let Some(captures) = HEAP_ADDRESS_REGEX.captures(line) {
+                reserved_heap = bytes(&captures[1], Some("m"));
+            } else if let Some(captures) = PHYSICAL_MEMORY_REGEX.captures(line) {
+                log.physical_memory = bytes(&captures[1], Some("k"));
+            } else if let Some(captures) = CONTAINER_LIMIT_REGEX.captures(line) {
+                log.container_memory_limit =
+                    bytes(&captures[1], captures.get(2).map(|m| m.as_str()));
+            }
+        }
+
+        if let Some(command_line) = log.command_line.clone() {
+            log.heap_flags(&command_line);
+        }
+
+        log.heap.max = log.heap.max.or(reserved_heap);
+        log.cause = log.classify();
+
+        log
+    }
+
+    fn heap_flags(&mut self, flags: &str) {
+        for captures in HEAP_FLAG_REGEX.captures_iter(flags) {
+            let Some(size) = bytes(&captures[2], captures.get(3).map(|m| m.as_str())) else {
+                continue;
+            };
+            let size = Some(size);
+
+            match &captures[1] {
+                "x" => self.heap.max = size,
+                _ => self.heap.initial = size,
+            }
+        }
+    }
+
+    /// The Java version, e.g. `17.0.8` or `1.8.0_381`.
+    pub fn java_version(&self) -> Option<&str> {
+        let jre_version = self.jre_version.as_deref()?;
+        let captures = JAVA_VERSION_REGEX.captures(jre_version)?;
+        Some(captures.get(1)?.as_str())
+    }
+
+    fn classify(&self) -> Option<FatalErrorCause> {
+        if self.out_of_memory.is_some() {
+            return match self.container_memory_limit {
+                Some(_) => Some(FatalErrorCause::ContainerOutOfMemory),
+                None => Some(FatalErrorCause::OutOfNativeMemory),
+            };
+        }
+
+        // The first native code outside the JVM and the OS, where the crash happened or what
+        // called into them. Frames below the first Java frame only called the Java code.
+        let culprit = self
+            .problematic_frame
+            .iter()
+            .chain(
+                self.native_frames
+                    .iter()
+                    .take_while(|frame| !matches!(frame.kind, 'j' | 'J')),
+            )
+            .filter(|frame| frame.kind == 'C')
+            .filter_map(|frame| frame.library.as_deref())
+            .map(str::to_lowercase)
+            .find(|library| !is_platform_library(library))?;
+
+        match is_any(&culprit, GPU_LIBRARIES) {
+            true => Some(FatalErrorCause::GpuDriver),
+            false => Some(FatalErrorCause::NativeLibrary),
+        }
+    }
+}
+
+fn is_any(library: &str, names: &[&str]) -> bool {
+    names.iter().any(|name| library.contains(name))
+}
+
+fn is_platform_library(library: &str) -> bool {
+    JVM_LAUNCHERS.contains(&library)
+        || is_any(library, JVM_LIBRARIES)
+        || is_any(library, SYSTEM_LIBRARIES)
+}
+
+/// Parses a size with an optional `k`, `m`, `g` or `t` unit into bytes, `None` when it
+/// overflows.
+fn bytes(size: &str, unit: Option<&str>) -> Option<u64> {
+    let multiplier = match unit.map(|unit| unit.to_ascii_lowercase()).as_deref() {
+        Some("k") => 1024,
+        Some("m") => 1024 * 1024,
+        Some("g") => 1024 * 1024 * 1024,
+        Some("t") => 1024 * 1024 * 1024 * 1024,
+        _ => 1,
+    };
+    size.parse::<u64>().ok()?.checked_mul(multiplier)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(log: &str) -> FatalErrorLog {
+        let lines: Vec<String> = log.lines().map(|line| line.trim().to_string()).collect();
+        FatalErrorLog::find(&lines).unwrap()
+    }
+
+    #[test]
+    fn jvm_crashes_have_no_culprit() {
+        let log = parse(
+            "#\n\
+             # A fatal error has been detected by the Java Runtime Environment:\n\
+             #  SIGSEGV (0xb) at pc=0x00007f8b2c1d2e5a, pid=12345, tid=12346\n\
+             # Problematic frame:\n\
+             # V  [libjvm.so+0x7a1b2c]  G1ParScanThreadState::trim_queue+0x2c\n\
+             \n\
+             Native frames: (J=compiled Java code, j=interpreted, Vv=VM code, C=native code)\n\
+             V  [libjvm.so+0x7a1b2c]  G1ParScanThreadState::trim_queue+0x2c\n\
+             C  [libstdc++.so.6+0xd6de4]\n\
+             C  [libgcc_s.so.1+0x1234]\n\
+             C  [libjimage.so+0x3a1c]\n\
+             C  [libawt_xawt.so+0x4c11]\n\
+             C  [libjli.so+0x9d99]  JavaMain+0x89\n\
+             C  [java+0x1261]  JavaMain+0x91\n\
+             C  [libc.so.6+0x94ac3]\n",
+        );
+
+        assert_eq!(log.cause, None);
+    }
+
+    #[test]
+    fn culprit_below_system_library() {
+        let log = parse(
+            "# A fatal error has been detected by the Java Runtime Environment:\n\
+             #  EXCEPTION_ACCESS_VIOLATION (0xc0000005) at pc=0x00007ffc2d4b6e5a, pid=1234, tid=5678\n\
+             # Problematic frame:\n\
+             # C  [ntdll.dll+0x1e5a]\n\
+             \n\
+             Native frames: (J=compiled Java code, j=interpreted, Vv=VM code, C=native code)\n\
+             C  [ntdll.dll+0x1e5a]\n\
+             C  [nvoglv64.dll+0xb4d6e0]\n\
+             j  org.lwjgl.opengl.GL11C.nglDrawElements(IIIJ)V+0\n\
+             C  [libnetty_transport_native_epoll_x86_64.so+0x1a2b]\n",
+        );
+
+        assert_eq!(log.cause, Some(FatalErrorCause::GpuDriver));
+    }
+
+    #[test]
+    fn callers_of_java_code_are_ignored() {
+        let log = parse(
+            "# A fatal error has been detected by the Java Runtime Environment:\n\
+             #  SIGSEGV (0xb) at pc=0x00007f8b2c1d2e5a, pid=12345, tid=12346\n\
+             # Problematic frame:\n\
+             # V  [libjvm.so+0x7a1b2c]\n\
+             \n\
+             Native frames: (J=compiled Java code, j=interpreted, Vv=VM code, C=native code)\n\
+             V  [libjvm.so+0x7a1b2c]\n\
+             J 1234 c2 net.minecraft.Foo.bar(I)V (42 bytes) @ 0x00007f8b2c1d2e5a\n\
+             C  [libnetty_transport_native_epoll_x86_64.so+0x1a2b]\n",
+        );
+
+        assert_eq!(log.cause, None);
+    }
+
+    #[test]
+    fn overflowing_sizes() {
+        let log = parse(
+            "# A fatal error has been detected by the Java Runtime Environment:\n\
+             Command Line: -Xms512m -Xmx99999999999999T nogui\n\
+             memory_limit_in_bytes: 99999999999999999 k\n",
+        );
+
+        assert_eq!(log.heap.initial, Some(512 * 1024 * 1024));
+        assert_eq!(log.heap.max, None);
+        assert_eq!(log.container_memory_limit, None);
+        assert_eq!(bytes("4", Some("G")), Some(4 * 1024 * 1024 * 1024));
+        assert_eq!(bytes("18446744073709551615", None), Some(u64::MAX));
+    }
+}
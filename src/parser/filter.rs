@@ -0,0 +1,39 @@
+use super::{header::name_matches, parser::LogEntry};
+
+/// Selects entries by thread and logger name.
+///
+/// A name matches if it's the same or starts with it, so `Netty Epoll Server IO` selects
+/// every `Netty Epoll Server IO #N` thread. Empty lists don't filter anything.
+#[derive(Clone, Debug, Default)]
+pub struct EntryFilter {
+    pub threads: Vec<String>,
+    pub loggers: Vec<String>,
+}
+
+impl EntryFilter {
+    pub fn thread(mut self, thread: impl Into<String>) -> Self {
+        self.threads.push(thread.into());
+        self
+    }
+
+    pub fn logger(mut self, logger: impl Into<String>) -> Self {
+        self.loggers.push(logger.into());
+        self
+    }
+
+    pub fn matches(&self, entry: &LogEntry) -> bool {
+        Self::any_matches(&self.threads, entry.thread.as_deref())
+            && Self::any_matches(&self.loggers, entry.logger.as_deref())
+    }
+
+    fn any_matches(filters: &[String], name: Option<&str>) -> bool {
+        if filters.is_empty() {
+            return true;
+        }
+
+        match name {
+            Some(name) => filters.iter().any(|filter| name_matches(name, filter)),
+            None => false,
+        }
+    }
+}
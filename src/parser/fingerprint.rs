@@ -0,0 +1,158 @@
+use super::{parser::LogEntry, stacktrace::StackTrace, timestamp::Timestamp};
+use regex::Regex;
+use serde::{Serialize, Serializer};
+use std::{borrow::Cow, collections::HashMap, sync::LazyLock};
+
+/// Frames of each exception in the cause chain that go into a [`Fingerprint`].
+pub const DEFAULT_FINGERPRINT_FRAMES: usize = 5;
+
+// Parts of generated class and method names that change between runs:
+// Foo$$Lambda$123/0x0000000800c0b000, Foo$1, $Proxy42, GeneratedMethodAccessor17, lambda$onEnable$0
+static GENERATED_NAME_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"/0x[0-9a-fA-F]+|(\$|Proxy|Accessor)\d+")
+        .unwrap_or_else(|e| panic!("Failed to create 'GENERATED_NAME_REGEX': {}", e))
+});
+
+fn normalize(name: &str) -> Cow<'_, str> {
+    GENERATED_NAME_REGEX.replace_all(name, "${1}")
+}
+
+/// Identifies an exception by its classes and top frames, ignoring messages and line numbers,
+/// so the same error repeated by a broken plugin gets the same fingerprint.
+///
+/// Serialized as a hex string, JavaScript numbers can't hold all 64 bits.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Fingerprint(pub u64);
+
+impl std::fmt::Display for Fingerprint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:016x}", self.0)
+    }
+}
+
+impl Serialize for Fingerprint {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+/// 64-bit FNV-1a
+struct Fnv1a(u64);
+
+impl Fnv1a {
+    fn new() -> Self {
+        Self(0xcbf29ce484222325)
+    }
+
+    fn write(&mut self, part: &str) {
+        // Separator, so `ab` + `c` and `a` + `bc` differ
+        for byte in part.bytes().chain([0]) {
+            self.0 ^= u64::from(byte);
+            self.0 = self.0.wrapping_mul(0x100000001b3);
+        }
+    }
+}
+
+impl StackTrace {
+    /// Fingerprint over every exception in the cause chain and its top `frames` frames.
+    pub fn fingerprint(&self, frames: usize) -> Fingerprint {
+        let mut hasher = Fnv1a::new();
+
+        for trace in self.causes() {
+            hasher.write(&normalize(&trace.class));
+
+            for frame in trace.frames.iter().take(frames) {
+                hasher.write(&normalize(&frame.class));
+                hasher.write(&normalize(&frame.method));
+            }
+        }
+
+        Fingerprint(hasher.0)
+    }
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ExceptionGroup {
+    pub fingerprint: Fingerprint,
+    pub count: usize,
+    pub first_line: usize,
+    pub last_line: usize,
+    pub first_timestamp: Option<Timestamp>,
+    pub last_timestamp: Option<Timestamp>,
+    /// First entry with the exception
+    pub sample: LogEntry,
+}
+
+/// Groups entries by the [`Fingerprint`] of their stack trace.
+///
+/// Only the first entry of each group is kept, so it can be fed a whole log entry by entry,
+/// e.g. from a [`ParserStream`](super::stream::ParserStream).
+pub struct ExceptionGroups {
+    frames: usize,
+    groups: Vec<ExceptionGroup>,
+    indices: HashMap<Fingerprint, usize>,
+}
+
+impl Default for ExceptionGroups {
+    fn default() -> Self {
+        Self::new(DEFAULT_FINGERPRINT_FRAMES)
+    }
+}
+
+impl ExceptionGroups {
+    pub fn new(frames: usize) -> Self {
+        Self {
+            frames,
+            groups: Vec::new(),
+            indices: HashMap::new(),
+        }
+    }
+
+    /// Adds an entry, entries without a stack trace are ignored.
+    pub fn push(&mut self, entry: LogEntry) {
+        let Some(trace) = &entry.stack_trace else {
+            return;
+        };
+
+        let fingerprint = trace.fingerprint(self.frames);
+
+        match self.indices.get(&fingerprint) {
+            Some(&idx) => {
+                let group = &mut self.groups[idx];
+                group.count += 1;
+                group.last_line = entry.start_line;
+                group.last_timestamp = entry.timestamp;
+            }
+            None => {
+                self.indices.insert(fingerprint, self.groups.len());
+                self.groups.push(ExceptionGroup {
+                    fingerprint,
+                    count: 1,
+                    first_line: entry.start_line,
+                    last_line: entry.start_line,
+                    first_timestamp: entry.timestamp,
+                    last_timestamp: entry.timestamp,
+                    sample: entry,
+                });
+            }
+        }
+    }
+
+    /// Groups in order of their first occurrence.
+    pub fn groups(&self) -> &[ExceptionGroup] {
+        &self.groups
+    }
+
+    pub fn into_groups(self) -> Vec<ExceptionGroup> {
+        self.groups
+    }
+}
+
+impl Extend<LogEntry> for ExceptionGroups {
+    fn extend<T: IntoIterator<Item = LogEntry>>(&mut self, entries: T) {
+        for entry in entries {
+            self.push(entry);
+        }
+    }
+}
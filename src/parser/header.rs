@@ -0,0 +1,321 @@
+use super::{
+    delimiters::{
+        DelimiterType, DEBUG_TOKENS, ERROR_TOKENS, FATAL_TOKENS, INFO_TOKENS, TRACE_TOKENS,
+        WARN_TOKENS,
+    },
+    log_level::{CustomLevel, EntryLevel},
+};
+use regex::Regex;
+use std::sync::LazyLock;
+
+// `docker logs --timestamps` and docker compose prefixes, see `timestamp`
+const CONTAINER_PATTERN: &str =
+    r"(?:\d{4}-\d{2}-\d{2}T[\d:.]+(?:Z|[+-]\d{2}:?\d{2})\s+)?(?:[\w.-]+\s+\|\s?)?";
+
+// [12:34:56], [12Jan2024 12:34:56.789], 2024-01-12 12:34:56,789, 12:34:56
+const TIMESTAMP_PATTERN: &str = r"(?:\[\d[^\[\]]*\]|\d{4}-\d{2}-\d{2}[ T]\d{2}:\d{2}:\d{2}(?:[.,]\d+)?|\d{1,2}:\d{2}:\d{2}(?:[.,]\d+)?)";
+
+// JUL's SimpleFormatter puts the level on the second line:
+// Jan 12, 2024 12:34:56 PM org.bukkit.Foo bar
+// WARNING: ...
+static JUL_HEADER_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^[A-Z][a-z]{2} \d{1,2}, \d{4} \d{1,2}:\d{2}:\d{2} [AP]M \S")
+        .unwrap_or_else(|e| panic!("Failed to create 'JUL_HEADER_REGEX': {}", e))
+});
+
+// [Server thread/INFO], [12:34:56 INFO], [INFO], custom levels like [Server thread/AUDIT]
+static LEVEL_HEADER_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\[(?:([^\[\]]+)/|[^\[\]]*?\b)[A-Z]+\]")
+        .unwrap_or_else(|e| panic!("Failed to create 'LEVEL_HEADER_REGEX': {}", e))
+});
+
+// Forge: [main/INFO] [minecraft/DedicatedServer]:, Fabric: [main/INFO] (FabricLoader),
+// plugins: [Server thread/INFO]: [LuckPerms] ...
+static LOGGER_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(?:\s*\[([^\[\]]+)\]|\s*\(([^()\s]+)\)|:\s*\[([\w.-]+)\]\s)")
+        .unwrap_or_else(|e| panic!("Failed to create 'LOGGER_REGEX': {}", e))
+});
+
+/// Thread and logger names from the header of an entry's first line.
+///
+/// `header_end` is the length of the entry's prefix, a match after it is part of the message.
+pub(crate) fn thread_and_logger(line: &str, header_end: usize) -> (Option<String>, Option<String>) {
+    let Some(captures) = LEVEL_HEADER_REGEX.captures(line) else {
+        return (None, None);
+    };
+
+    let Some(header) = captures.get(0).filter(|m| m.start() < header_end) else {
+        return (None, None);
+    };
+
+    let thread = captures.get(1).map(|m| m.as_str().trim().to_string());
+
+    let logger = LOGGER_REGEX
+        .captures(&line[header.end()..])
+        .and_then(|captures| captures.iter().skip(1).flatten().next())
+        .map(|m| m.as_str().trim().to_string());
+
+    (thread, logger)
+}
+
+/// Whether `name` is `filter` or starts with it, so `Craft Scheduler Thread` matches
+/// `Craft Scheduler Thread - 12` and `minecraft` matches `minecraft/DedicatedServer`.
+pub(crate) fn name_matches(name: &str, filter: &str) -> bool {
+    match name.strip_prefix(filter) {
+        Some(rest) => !rest.starts_with(|c: char| c.is_alphanumeric()),
+        None => false,
+    }
+}
+
+/// Level of a line, found in its header.
+pub(crate) struct HeaderLevel {
+    pub(crate) level: EntryLevel,
+    /// The level as written, e.g. `SEVERE` for [`EntryLevel::Error`]
+    pub(crate) token: String,
+    /// Name of the [`CustomLevel`] the token belongs to
+    pub(crate) custom_level: Option<String>,
+    /// The level, or the severity of its custom level
+    pub(crate) severity: EntryLevel,
+    /// End of the delimiter following the level, where the message starts
+    pub(crate) end: usize,
+    pub(crate) format: DelimiterType,
+}
+
+/// Finds the level where Log4j and JUL layouts put it, instead of anywhere in the line.
+///
+/// The header is an optional container prefix, then timestamps (and a thread name after
+/// them, `2024-01-12 12:34:56 [main] INFO`), or anything without letters for unknown
+/// layouts. The level is the first token after it, either bare (`INFO:`, `INFO `) or closing a
+/// bracket (`[Server thread/INFO]:`, `[12:34:56 INFO]:`, `[INFO]`). So `<Steve> I got
+/// ERROR: something` in a message is never taken for a level, and neither is an indented
+/// continuation line like `    WARN ...`.
+///
+/// Without a timestamp or bracket, only an uppercase level at the start of the line followed
+/// by `: ` is a header, like the `INFO: ...` lines of server wrappers and JUL.
+pub(crate) struct LevelGrammar {
+    regex: Regex,
+    custom_levels: Vec<CustomLevel>,
+}
+
+impl LevelGrammar {
+    pub(crate) fn new(custom_levels: &[CustomLevel]) -> Self {
+        let mut tokens: Vec<&str> = TRACE_TOKENS
+            .iter()
+            .chain(DEBUG_TOKENS)
+            .chain(INFO_TOKENS)
+            .chain(WARN_TOKENS)
+            .chain(ERROR_TOKENS)
+            .chain(FATAL_TOKENS)
+            .copied()
+            .chain(
+                custom_levels
+                    .iter()
+                    .flat_map(|level| level.tokens.iter().map(String::as_str))
+                    // Levels built in code aren't validated like configured ones
+                    .filter(|token| !token.trim().is_empty()),
+            )
+            .collect();
+
+        // Longest first, so `WARN` doesn't stop at the start of `WARNING`
+        tokens.sort_by_key(|token| std::cmp::Reverse(token.len()));
+
+        let tokens = tokens
+            .iter()
+            .map(|token| regex::escape(token))
+            .collect::<Vec<_>>()
+            .join("|");
+
+        let pattern = format!(
+            r"^{CONTAINER_PATTERN}(?:(?P<stamp>(?:{TIMESTAMP_PATTERN}[ \t]*(?:[|-][ \t]*)?)+?(?:\[[^\[\]]*\][ \t]*)??)|(?P<lead>[^\p{{L}}\[]*?))(?P<bracket>\[(?:[^\[\]]*[/ ])?)?(?P<level>{tokens})(?:(?P<delimiter>\]:|\]|:)|\b)"
+        );
+
+        let regex = Regex::new(&pattern).unwrap_or_else(|e| {
+            panic!("Failed to create level grammar: {}", e);
+        });
+
+        Self {
+            regex,
+            custom_levels: custom_levels.to_vec(),
+        }
+    }
+
+    pub(crate) fn level(&self, line: &str) -> Option<HeaderLevel> {
+        let captures = self.regex.captures(line)?;
+        let token = captures.name("level")?.as_str();
+
+        // Indented lines are continuations, e.g. `    WARN ...` in a multi-line message
+        let lead = captures.name("lead").map_or("", |m| m.as_str());
+        if lead.starts_with(char::is_whitespace) {
+            return None;
+        }
+
+        let end = captures.get(0)?.end();
+        let bare = captures.name("bracket").is_none() && captures.name("stamp").is_none();
+        let delimiter = captures.name("delimiter").map(|m| m.as_str());
+
+        if bare
+            && !(lead.is_empty()
+                && delimiter == Some(":")
+                && matches!(line[end..].chars().next(), None | Some(' '))
+                && !token.chars().any(char::is_lowercase))
+        {
+            return None;
+        }
+
+        let format = match delimiter {
+            Some("]:") => DelimiterType::BracketColon,
+            Some("]") => DelimiterType::Bracket,
+            Some(":") => DelimiterType::Colon,
+            _ => DelimiterType::NoColonNoBracket,
+        };
+
+        let (level, custom_level) = self.token_level(token);
+
+        Some(HeaderLevel {
+            level,
+            token: token.to_string(),
+            custom_level: custom_level.map(|custom| custom.name.clone()),
+            severity: custom_level.map_or(level, |custom| custom.severity),
+            end,
+            format,
+        })
+    }
+
+    /// Level token of a structured record's level field, JSON template layouts may write `info`.
+    pub(crate) fn record_token(&self, level: &str) -> String {
+        match self.entry_level(level) {
+            EntryLevel::Unknown => level.to_uppercase(),
+            _ => level.to_string(),
+        }
+    }
+
+    /// Level of a token, and its custom level for [`EntryLevel::Custom`].
+    pub(crate) fn token_level(&self, token: &str) -> (EntryLevel, Option<&CustomLevel>) {
+        let level = self.entry_level(token);
+
+        match level {
+            EntryLevel::Custom => (level, self.custom_level(token)),
+            _ => (level, None),
+        }
+    }
+
+    fn entry_level(&self, token: &str) -> EntryLevel {
+        if TRACE_TOKENS.contains(&token) {
+            EntryLevel::Trace
+        } else if DEBUG_TOKENS.contains(&token) {
+            EntryLevel::Debug
+        } else if INFO_TOKENS.contains(&token) {
+            EntryLevel::Info
+        } else if WARN_TOKENS.contains(&token) {
+            EntryLevel::Warn
+        } else if ERROR_TOKENS.contains(&token) {
+            EntryLevel::Error
+        } else if FATAL_TOKENS.contains(&token) {
+            EntryLevel::Fatal
+        } else if self.custom_level(token).is_some() {
+            EntryLevel::Custom
+        } else {
+            EntryLevel::Unknown
+        }
+    }
+
+    fn custom_level(&self, token: &str) -> Option<&CustomLevel> {
+        self.custom_levels
+            .iter()
+            .find(|level| level.tokens.iter().any(|custom| custom == token))
+    }
+}
+
+pub(crate) fn is_jul_header(line: &str) -> bool {
+    JUL_HEADER_REGEX.is_match(line)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn level(line: &str) -> Option<HeaderLevel> {
+        LevelGrammar::new(&[]).level(line)
+    }
+
+    #[test]
+    fn chat_message_keeps_header_level() {
+        let header =
+            level("[12:34:56] [Server thread/INFO]: <Steve> I got ERROR: something").unwrap();
+
+        assert_eq!(header.level, EntryLevel::Info);
+        assert_eq!(header.token, "INFO");
+        assert_eq!(header.format, DelimiterType::BracketColon);
+    }
+
+    #[test]
+    fn level_in_message_is_ignored() {
+        assert!(level("<Steve> who set the WARN] threshold").is_none());
+        assert!(level("Caused by: java.lang.IllegalStateException: ERROR: bad state").is_none());
+    }
+
+    #[test]
+    fn indented_continuation_has_no_level() {
+        assert!(level("    WARN continuation").is_none());
+        assert!(level("\tERROR: at the start of a tab-indented line").is_none());
+    }
+
+    #[test]
+    fn bare_level() {
+        let header = level("INFO: Starting the wrapper").unwrap();
+
+        assert_eq!(header.level, EntryLevel::Info);
+        assert_eq!(header.format, DelimiterType::Colon);
+        assert!(level("WARN:").is_some());
+
+        let header = level("2024-01-12 12:34:56 INFO Starting the wrapper").unwrap();
+        assert_eq!(header.format, DelimiterType::NoColonNoBracket);
+
+        // Not a wrapper header, part of a message
+        assert!(level("INFO Starting the wrapper").is_none());
+        assert!(level("INFO:Starting the wrapper").is_none());
+        assert!(level("> INFO: Starting the wrapper").is_none());
+    }
+
+    #[test]
+    fn jul_layout() {
+        assert!(is_jul_header(
+            "Jan 12, 2024 12:34:56 PM org.bukkit.craftbukkit.Main main"
+        ));
+        assert!(!is_jul_header("[12:34:56] [Server thread/INFO]: Done"));
+
+        let header = level("WARNING: Could not load the plugin").unwrap();
+        assert_eq!(header.level, EntryLevel::Warn);
+        assert_eq!(header.token, "WARNING");
+    }
+
+    #[test]
+    fn blank_custom_tokens_are_ignored() {
+        let grammar = LevelGrammar::new(&[CustomLevel::from(""), CustomLevel::from("AUDIT")]);
+
+        assert!(grammar.level("Just a message").is_none());
+        assert_eq!(
+            grammar.level("[12:34:56 AUDIT]: Login").unwrap().level,
+            EntryLevel::Custom
+        );
+    }
+
+    #[test]
+    fn jul_levels() {
+        let levels = [
+            ("SEVERE", EntryLevel::Error),
+            ("WARNING", EntryLevel::Warn),
+            ("INFO", EntryLevel::Info),
+            ("CONFIG", EntryLevel::Debug),
+            ("FINE", EntryLevel::Debug),
+            ("FINER", EntryLevel::Trace),
+            ("FINEST", EntryLevel::Trace),
+        ];
+
+        for (token, expected) in levels {
+            let header = level(&format!("{token}: message")).unwrap();
+            assert_eq!(header.level, expected, "{token}");
+            assert_eq!(header.token, token);
+        }
+    }
+}
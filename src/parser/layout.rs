@@ -0,0 +1,383 @@
+use super::{
+    stacktrace::{StackFrame, StackTrace},
+    timestamp::Timestamp,
+};
+use chrono::DateTime;
+use quick_xml::events::{BytesStart, Event as XmlEvent};
+use serde_json::{Map, Value};
+
+/// How the log file is written, Log4j2's `JsonLayout` and `XmlLayout` (and ECS-style JSON
+/// layouts) write one structured record per event instead of a line.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub(crate) enum Layout {
+    Text,
+    Json,
+    Xml,
+}
+
+impl Layout {
+    /// Detects the layout from the log's first line.
+    pub(crate) fn detect(line: &str) -> Self {
+        let line = line.trim();
+
+        // `[` starts a `complete="true"` layout, `{"` a compact one
+        if matches!(line, "[" | "{") || line.starts_with("{\"") || line.starts_with("[{") {
+            Layout::Json
+        } else if line.starts_with("<?xml") || line.starts_with("<Event") {
+            Layout::Xml
+        } else {
+            Layout::Text
+        }
+    }
+
+    pub(crate) fn is_structured(&self) -> bool {
+        !matches!(self, Layout::Text)
+    }
+
+    /// Lines between records: the `[`, `]` and `,` of a complete JSON layout, the XML
+    /// declaration and `<Events>` root.
+    pub(crate) fn is_framing(&self, line: &str) -> bool {
+        let line = line.trim();
+
+        match self {
+            Layout::Text => false,
+            Layout::Json => matches!(line, "" | "[" | "]" | ","),
+            Layout::Xml => {
+                line.is_empty()
+                    || line.starts_with("<?xml")
+                    || line.starts_with("<Events")
+                    || line.starts_with("</Events")
+            }
+        }
+    }
+}
+
+/// Tells when a record spanning several lines is complete.
+#[derive(Default)]
+pub(crate) struct RecordScanner {
+    /// Nesting of JSON objects and arrays
+    depth: usize,
+    in_string: bool,
+    escaped: bool,
+    started: bool,
+}
+
+impl RecordScanner {
+    /// Feeds the next line of a record, returns whether the record is complete.
+    pub(crate) fn feed(&mut self, layout: Layout, line: &str) -> bool {
+        match layout {
+            Layout::Text => true,
+            Layout::Json => self.feed_json(line),
+            // `<Event .../>` or the closing tag, `</Event>` in a message is escaped
+            Layout::Xml => {
+                let first = !self.started;
+                self.started = true;
+
+                line.contains("</Event>")
+                    || (first && line.trim().starts_with("<Event") && line.trim().ends_with("/>"))
+            }
+        }
+    }
+
+    fn feed_json(&mut self, line: &str) -> bool {
+        for c in line.chars() {
+            if self.in_string {
+                match c {
+                    _ if self.escaped => self.escaped = false,
+                    '\\' => self.escaped = true,
+                    '"' => self.in_string = false,
+                    _ => {}
+                }
+                continue;
+            }
+
+            match c {
+                '"' => self.in_string = true,
+                '{' | '[' => {
+                    self.depth += 1;
+                    self.started = true;
+                }
+                '}' | ']' => self.depth = self.depth.saturating_sub(1),
+                _ => {}
+            }
+        }
+
+        self.started && self.depth == 0
+    }
+}
+
+/// Fields of a structured record.
+pub(crate) struct Event {
+    /// The level as written, e.g. `WARN`
+    pub(crate) level: String,
+    pub(crate) thread: Option<String>,
+    pub(crate) logger: Option<String>,
+    pub(crate) timestamp: Option<Timestamp>,
+    pub(crate) message: String,
+    /// The thrown exception, formatted like it's printed in text logs
+    pub(crate) thrown: Option<String>,
+}
+
+impl Event {
+    /// Parses a record, `None` when it isn't a Log4j event.
+    pub(crate) fn parse(layout: Layout, record: &str) -> Option<Self> {
+        let value = match layout {
+            Layout::Text => return None,
+            Layout::Json => {
+                // Separators of a complete layout: `, {` ... `}`
+                let start = record.find('{')?;
+                let end = record.rfind('}')?;
+                serde_json::from_str(record.get(start..=end)?).ok()?
+            }
+            Layout::Xml => xml_value(record)?,
+        };
+
+        Self::from_value(&value)
+    }
+
+    fn from_value(value: &Value) -> Option<Self> {
+        let level = string(value, &["level", "log.level", "severity"])?;
+
+        let thread = string(value, &["thread", "threadName", "process.thread.name"]);
+        let logger = string(
+            value,
+            &["loggerName", "log.logger", "logger_name", "logger"],
+        );
+
+        let message = match field(value, &["message"]) {
+            Some(Value::String(message)) => message.clone(),
+            Some(Value::Null) | None => String::new(),
+            Some(message) => message.to_string(),
+        };
+
+        let thrown = match field(value, &["thrown"]) {
+            Some(thrown) => thrown_text(thrown),
+            // ECS and other template layouts print the trace as text
+            None => string(
+                value,
+                &["error.stack_trace", "exception.stacktrace", "exception"],
+            ),
+        };
+
+        Some(Self {
+            level,
+            thread,
+            logger,
+            timestamp: timestamp(value),
+            message,
+            thrown,
+        })
+    }
+}
+
+/// First of `names` in the object, either as a key (`log.level`) or a path to a nested
+/// object (`{"log": {"level": ...}}`).
+fn field<'a>(value: &'a Value, names: &[&str]) -> Option<&'a Value> {
+    names.iter().find_map(|name| {
+        value.get(name).or_else(|| {
+            name.split('.')
+                .try_fold(value, |value, part| value.get(part))
+        })
+    })
+}
+
+fn string(value: &Value, names: &[&str]) -> Option<String> {
+    match field(value, names)? {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+// XML attributes are always strings
+fn number(value: &Value, names: &[&str]) -> Option<i64> {
+    match field(value, names)? {
+        Value::Number(n) => n.as_i64(),
+        Value::String(s) => s.parse().ok(),
+        _ => None,
+    }
+}
+
+/// `instant` with nanoseconds since Log4j 2.11, `timeMillis` before, `@timestamp` in ECS.
+///
+/// Structured layouts write UTC, like container timestamps it's kept as is.
+fn timestamp(value: &Value) -> Option<Timestamp> {
+    let date_time = if let Some(instant) = field(value, &["instant"]) {
+        let seconds = number(instant, &["epochSecond"])?;
+        let nanos = number(instant, &["nanoOfSecond"]).unwrap_or(0);
+        DateTime::from_timestamp(seconds, u32::try_from(nanos).ok()?)?.naive_utc()
+    } else if let Some(millis) = number(value, &["timeMillis"]) {
+        DateTime::from_timestamp_millis(millis)?.naive_utc()
+    } else {
+        let timestamp = string(value, &["@timestamp", "timestamp"])?;
+        DateTime::parse_from_rfc3339(&timestamp).ok()?.naive_utc()
+    };
+
+    Some(Timestamp::DateTime(date_time))
+}
+
+/// Formats a `thrown` object, or returns it as is when written with `stacktraceAsString`.
+fn thrown_text(thrown: &Value) -> Option<String> {
+    if let Some(Value::String(text)) = field(thrown, &["extendedStackTrace"]) {
+        return Some(text.clone());
+    }
+
+    Some(thrown_trace(thrown)?.to_string())
+}
+
+fn thrown_trace(thrown: &Value) -> Option<StackTrace> {
+    let class = string(thrown, &["name"])?;
+    let message = string(thrown, &["message", "localizedMessage"]);
+
+    let mut trace = StackTrace::new(&class, message.as_deref());
+
+    if let Some(Value::Array(items)) = field(thrown, &["extendedStackTrace", "stackTrace"]) {
+        trace.frames = items.iter().filter_map(frame).collect();
+    }
+
+    trace.elided_frames = number(thrown, &["commonElementCount"])
+        .and_then(|count| usize::try_from(count).ok())
+        .unwrap_or(0);
+
+    trace.cause = field(thrown, &["cause"])
+        .and_then(thrown_trace)
+        .map(Box::new);
+
+    if let Some(Value::Array(suppressed)) = field(thrown, &["suppressed"]) {
+        trace.suppressed = suppressed.iter().filter_map(thrown_trace).collect();
+    }
+
+    Some(trace)
+}
+
+fn frame(item: &Value) -> Option<StackFrame> {
+    let location = string(item, &["location"]);
+    let version = string(item, &["version"]).unwrap_or_else(|| "?".to_string());
+
+    Some(StackFrame {
+        class: string(item, &["class"])?,
+        method: string(item, &["method"])?,
+        file: string(item, &["file"]),
+        // -1 unknown, -2 native
+        line: number(item, &["line"]).and_then(|line| u32::try_from(line).ok()),
+        module: None,
+        class_loader: None,
+        // Printed as `~[location:version]` in text logs
+        jar: location.map(|location| format!("{location}:{version}")),
+    })
+}
+
+/// Converts an `XmlLayout` record to the shape of the JSON layout: elements are named like
+/// the JSON fields (`Thrown` is `thrown`), attributes become fields, elements with only text
+/// become strings, and lists (`ExtendedStackTrace` of `ExtendedStackTraceItem`s) arrays.
+fn xml_value(record: &str) -> Option<Value> {
+    let mut reader = quick_xml::Reader::from_str(record);
+
+    // Elements being read, the root `Event` first
+    let mut stack: Vec<XmlElement> = Vec::new();
+
+    loop {
+        match reader.read_event().ok()? {
+            XmlEvent::Start(start) => stack.push(XmlElement::new(&start)?),
+            XmlEvent::Empty(start) => {
+                let element = XmlElement::new(&start)?;
+
+                match stack.last_mut() {
+                    Some(parent) => parent.children.push(element),
+                    None => return Some(element.into_value()),
+                }
+            }
+            XmlEvent::Text(text) => {
+                if let Some(element) = stack.last_mut() {
+                    element.text.push_str(&text.unescape().ok()?);
+                }
+            }
+            XmlEvent::CData(data) => {
+                if let Some(element) = stack.last_mut() {
+                    element.text.push_str(std::str::from_utf8(&data).ok()?);
+                }
+            }
+            XmlEvent::End(_) => {
+                let element = stack.pop()?;
+
+                match stack.last_mut() {
+                    Some(parent) => parent.children.push(element),
+                    None => return Some(element.into_value()),
+                }
+            }
+            XmlEvent::Eof => return None,
+            _ => {}
+        }
+    }
+}
+
+struct XmlElement {
+    name: String,
+    attributes: Map<String, Value>,
+    children: Vec<XmlElement>,
+    text: String,
+}
+
+impl XmlElement {
+    fn new(start: &BytesStart) -> Option<Self> {
+        let name = std::str::from_utf8(start.local_name().as_ref())
+            .ok()?
+            .to_string();
+
+        let mut attributes = Map::new();
+
+        for attribute in start.attributes() {
+            let attribute = attribute.ok()?;
+            let key = std::str::from_utf8(attribute.key.local_name().as_ref())
+                .ok()?
+                .to_string();
+
+            // Namespace declarations
+            if attribute.key.as_ref().starts_with(b"xmlns") {
+                continue;
+            }
+
+            let value = attribute.unescape_value().ok()?.into_owned();
+            attributes.insert(key, Value::String(value));
+        }
+
+        Some(Self {
+            name,
+            attributes,
+            children: Vec::new(),
+            text: String::new(),
+        })
+    }
+
+    fn into_value(self) -> Value {
+        if self.attributes.is_empty() && self.children.is_empty() {
+            return Value::String(self.text.trim().to_string());
+        }
+
+        if self.attributes.is_empty() && self.children.iter().all(|child| child.is_item()) {
+            return Value::Array(self.children.into_iter().map(Self::into_value).collect());
+        }
+
+        let mut object = self.attributes;
+
+        for child in self.children {
+            object.insert(json_name(&child.name), child.into_value());
+        }
+
+        Value::Object(object)
+    }
+
+    fn is_item(&self) -> bool {
+        self.name.ends_with("Item")
+    }
+}
+
+// Thrown -> thrown, ExtendedStackTrace -> extendedStackTrace
+fn json_name(name: &str) -> String {
+    let mut chars = name.chars();
+
+    match chars.next() {
+        Some(first) => first.to_lowercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
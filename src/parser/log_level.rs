@@ -1,10 +1,15 @@
+use serde::{de, Deserialize, Deserializer, Serialize};
 use std::fmt::Formatter;
 
-#[derive(Clone, Copy, PartialEq, Debug)]
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "lowercase")]
 pub enum EntryLevel {
+    Trace,
+    Debug,
     Info,
     Warn,
     Error,
+    Fatal,
     Custom,
     Unknown,
 }
@@ -12,11 +17,158 @@ pub enum EntryLevel {
 impl std::fmt::Display for EntryLevel {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
+            EntryLevel::Trace => write!(f, "t"),
+            EntryLevel::Debug => write!(f, "d"),
             EntryLevel::Info => write!(f, "i"),
             EntryLevel::Warn => write!(f, "w"),
             EntryLevel::Error => write!(f, "e"),
+            EntryLevel::Fatal => write!(f, "f"),
             EntryLevel::Custom => write!(f, "c"),
             EntryLevel::Unknown => write!(f, "u"),
         }
     }
 }
+
+/// A level a plugin or wrapper logs with besides the standard ones, e.g. `[AUDIT]` or
+/// `SUCCESS:`.
+///
+/// ```toml
+/// [[levels]]
+/// name = "success"
+/// tokens = ["SUCCESS", "OK"]
+/// severity = "info"
+/// color = "#2ecc71"
+/// ```
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct CustomLevel {
+    pub name: String,
+    /// The level as written in the log, matched case-sensitively
+    #[serde(deserialize_with = "CustomLevel::deserialize_tokens")]
+    pub tokens: Vec<String>,
+    /// Standard level the entries count as, e.g. for filtering and whether the whole entry
+    /// or only its prefix is coloured
+    #[serde(
+        default = "CustomLevel::default_severity",
+        deserialize_with = "CustomLevel::deserialize_severity"
+    )]
+    pub severity: EntryLevel,
+    /// CSS class of the entry's level, `c` when unset
+    pub class: Option<String>,
+    /// CSS colour of the entry's level
+    pub color: Option<String>,
+}
+
+impl CustomLevel {
+    fn default_severity() -> EntryLevel {
+        EntryLevel::Info
+    }
+
+    // A blank token would match every line
+    fn deserialize_tokens<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<String>, D::Error> {
+        let tokens = Vec::<String>::deserialize(deserializer)?;
+
+        if tokens.is_empty() {
+            return Err(de::Error::custom("a custom level needs at least one token"));
+        }
+
+        if let Some(token) = tokens.iter().find(|token| token.trim().is_empty()) {
+            return Err(de::Error::custom(format!(
+                "blank custom level token {token:?}"
+            )));
+        }
+
+        Ok(tokens)
+    }
+
+    fn deserialize_severity<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<EntryLevel, D::Error> {
+        match EntryLevel::deserialize(deserializer)? {
+            level @ (EntryLevel::Custom | EntryLevel::Unknown) => Err(de::Error::custom(format!(
+                "severity must be trace, debug, info, warn, error or fatal, not {}",
+                format!("{level:?}").to_lowercase()
+            ))),
+            level => Ok(level),
+        }
+    }
+
+    /// CSS class for the level in HTML output.
+    pub fn class(&self) -> String {
+        self.class
+            .clone()
+            .unwrap_or_else(|| EntryLevel::Custom.to_string())
+    }
+}
+
+/// A level named after its only token, e.g. `AUDIT`.
+impl From<&str> for CustomLevel {
+    fn from(token: &str) -> Self {
+        Self {
+            name: token.to_lowercase(),
+            tokens: vec![token.to_string()],
+            severity: CustomLevel::default_severity(),
+            class: None,
+            color: None,
+        }
+    }
+}
+
+impl From<String> for CustomLevel {
+    fn from(token: String) -> Self {
+        token.as_str().into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Deserialize)]
+    struct Levels {
+        levels: Vec<CustomLevel>,
+    }
+
+    fn parse(toml: &str) -> Result<Vec<CustomLevel>, toml::de::Error> {
+        toml::from_str::<Levels>(toml).map(|root| root.levels)
+    }
+
+    #[test]
+    fn configured_level() {
+        let levels = parse(
+            r##"
+            [[levels]]
+            name = "success"
+            tokens = ["SUCCESS", "OK"]
+            color = "#2ecc71"
+            "##,
+        )
+        .unwrap();
+
+        assert_eq!(levels[0].tokens, ["SUCCESS", "OK"]);
+        assert_eq!(levels[0].severity, EntryLevel::Info);
+        assert_eq!(levels[0].class(), "c");
+    }
+
+    #[test]
+    fn blank_tokens_are_rejected() {
+        assert!(parse("[[levels]]\nname = \"a\"\ntokens = [\"\"]").is_err());
+        assert!(parse("[[levels]]\nname = \"a\"\ntokens = [\"AUDIT\", \"  \"]").is_err());
+        assert!(parse("[[levels]]\nname = \"a\"\ntokens = []").is_err());
+    }
+
+    #[test]
+    fn severity_must_be_a_standard_level() {
+        let level = |severity: &str| {
+            parse(&format!(
+                "[[levels]]\nname = \"a\"\ntokens = [\"AUDIT\"]\nseverity = \"{severity}\""
+            ))
+        };
+
+        assert_eq!(level("warn").unwrap()[0].severity, EntryLevel::Warn);
+        assert!(level("custom").is_err());
+        assert!(level("unknown").is_err());
+        assert!(level("severe").is_err());
+    }
+}
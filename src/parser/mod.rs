@@ -1,4 +1,14 @@
+pub mod crash_report;
 pub mod delimiters;
+pub mod fatal_error;
+pub mod filter;
+pub mod fingerprint;
+mod header;
+pub(crate) mod layout;
 pub mod log_level;
 #[allow(clippy::module_inception)]
 pub mod parser;
+pub mod stacktrace;
+pub mod stream;
+pub mod thread_dump;
+pub mod timestamp;
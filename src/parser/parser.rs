@@ -1,234 +1,668 @@
-use super::{delimiters::detect_delimiter_type, log_level::EntryLevel};
-use crate::parser::delimiters::Delimiters;
+use super::{
+    delimiters::{format_segments, DelimiterType, FormatSegment},
+    filter::EntryFilter,
+    fingerprint::{ExceptionGroup, ExceptionGroups},
+    header::{is_jul_header, thread_and_logger, HeaderLevel, LevelGrammar},
+    layout::{Event, Layout, RecordScanner},
+    log_level::{CustomLevel, EntryLevel},
+    stacktrace::StackTrace,
+    stream::{ParserIter, ParserStream},
+    timestamp::{DateTracker, Timestamp},
+};
+use crate::{Error, Result};
+use chrono::NaiveDate;
+use serde::Serialize;
+use std::io;
+use tokio_stream::Stream;
+
+// Bounds on an entry's buffer, so a log without headers doesn't become one giant entry.
+// Larger entries are cut into several.
+const MAX_ENTRY_LINES: usize = 10_000;
+const MAX_ENTRY_BYTES: usize = 4 * 1024 * 1024;
 
 pub struct Parser {
     lines: Vec<String>,
-    delimiters: Delimiters,
+    custom_levels: Vec<CustomLevel>,
+    date: Option<NaiveDate>,
 }
 
-#[derive(Debug)]
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
 pub struct LogEntry {
-    log_level: EntryLevel,
-    prefix: String,
-    message: String,
+    pub log_level: EntryLevel,
+    /// The level as written in the log, e.g. `SEVERE` or `WARNING`
+    pub level_token: Option<String>,
+    /// Name of the [`CustomLevel`] for [`EntryLevel::Custom`] entries
+    pub custom_level: Option<String>,
+    /// Standard level the entry counts as: its own level, or the severity of its custom level
+    pub severity: EntryLevel,
+    /// Start of the first line up to and including the level delimiter, e.g. `[12:34:56 INFO]:`
+    pub prefix: String,
+    /// Rest of the entry, including any continuation lines
+    pub message: String,
+    pub timestamp: Option<Timestamp>,
+    /// e.g. `Server thread`, `Netty Epoll Server IO #1`
+    pub thread: Option<String>,
+    /// e.g. Forge's `minecraft/DedicatedServer`, or the plugin name in `[LuckPerms] ...`
+    pub logger: Option<String>,
+    /// Format of the level delimiter in the entry's first line, see [`FormatSegment`]. `None`
+    /// for entries of structured layouts
+    pub format: Option<DelimiterType>,
+    /// Java exception printed in the entry, if any
+    pub stack_trace: Option<StackTrace>,
+    /// 1-based number of the entry's first line in the log
+    pub start_line: usize,
+    /// 1-based number of the entry's last line, inclusive
+    pub end_line: usize,
+    /// Byte offset of the entry in the decoded text, the log's lines joined with `\n`.
+    ///
+    /// This is not an offset into the file: CRLF line endings, a BOM, gzip compression and
+    /// non-UTF-8 encodings all shift it. Use the line numbers to find an entry in the file.
+    /// Entries of structured layouts are rendered as text, their span is the record they were
+    /// read from
+    pub text_start: usize,
+    /// Exclusive end of the entry, see [`LogEntry::text_start`]
+    pub text_end: usize,
 }
 
-impl Parser {
-    pub fn new(lines: Vec<String>, custom_delimiters: Vec<String>) -> Self {
-        let delimiter_type = detect_delimiter_type(&lines);
-        let delimiters = Delimiters::new(custom_delimiters, delimiter_type);
-        Self { lines, delimiters }
+impl std::fmt::Display for LogEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}", self.prefix, self.message)
     }
+}
 
-    fn split(&self, chunk: String, delimiter: &str) -> (String, String) {
-        const EMPTY_TUPLE: (&str, &str) = ("", "");
+impl LogEntry {
+    /// `data-thread` and `data-logger` attributes, for colouring entries by them with CSS.
+    fn html_attributes(&self) -> String {
+        let mut attributes = String::new();
 
-        let split = chunk.split_once(delimiter).unwrap_or(EMPTY_TUPLE);
+        if let Some(thread) = &self.thread {
+            attributes.push_str(&format!(
+                r#" data-thread="{}""#,
+                html_escape::encode_double_quoted_attribute(thread)
+            ));
+        }
 
-        let prefix = format!("{}{}", split.0, delimiter);
-        let message = split.1.to_string();
+        if let Some(logger) = &self.logger {
+            attributes.push_str(&format!(
+                r#" data-logger="{}""#,
+                html_escape::encode_double_quoted_attribute(logger)
+            ));
+        }
 
-        (prefix, message)
+        attributes
     }
 
-    fn parse(&self) -> Vec<LogEntry> {
-        let mut log_entries = Vec::new();
+    fn html(&self, id: usize, custom_levels: &[CustomLevel]) -> String {
+        let attributes = self.html_attributes();
 
-        let mut last_idx_with_level: usize = 0;
-        let mut last_log_level = self.log_level(&self.lines[0]).0;
-
-        let last_line_idx = self.lines.len() - 1;
+        if let Some(level) = self.custom_level.as_ref().and_then(|name| {
+            custom_levels
+                .iter()
+                .find(|custom_level| &custom_level.name == name)
+        }) {
+            return self.custom_html(id, &attributes, level);
+        }
 
-        #[allow(unused_assignments)]
-        let mut prefix = String::new();
-        #[allow(unused_assignments)]
-        let mut message = String::new();
+        match self.log_level {
+            EntryLevel::Trace | EntryLevel::Debug | EntryLevel::Info => format!(
+                r#"<span class="p" id="L{id}"{attributes}><span class={}>{}</span>{}</span>"#,
+                self.log_level,
+                html_escape::encode_text(self.prefix.as_str()),
+                html_escape::encode_text(self.message.as_str())
+            ),
+            EntryLevel::Warn | EntryLevel::Error | EntryLevel::Fatal | EntryLevel::Unknown => {
+                format!(
+                    r#"<span class="p" id="L{id}"{attributes}><span class="{}">{}{}</span></span>"#,
+                    self.log_level,
+                    html_escape::encode_text(self.prefix.as_str()),
+                    html_escape::encode_text(self.message.as_str()),
+                )
+            }
+            EntryLevel::Custom => format!(
+                r#"<span class="p" id="L{id}"{attributes}><span class="{}">{}</span>{}</span>"#,
+                self.log_level,
+                html_escape::encode_text(self.prefix.as_str()),
+                html_escape::encode_text(self.message.as_str())
+            ),
+        }
+    }
 
-        for (index, line) in self.lines.iter().enumerate() {
-            let previous_line_idx = match index {
-                0 => 0,
-                _ => index - 1,
-            };
+    /// Custom levels get their own class and colour, and are coloured like their severity.
+    fn custom_html(&self, id: usize, attributes: &str, level: &CustomLevel) -> String {
+        let class = html_escape::encode_double_quoted_attribute(&level.class()).into_owned();
+        let style = match &level.color {
+            Some(color) => format!(
+                r#" style="color: {}""#,
+                html_escape::encode_double_quoted_attribute(color)
+            ),
+            None => String::new(),
+        };
+
+        let prefix = html_escape::encode_text(self.prefix.as_str());
+        let message = html_escape::encode_text(self.message.as_str());
+
+        match self.severity {
+            EntryLevel::Warn | EntryLevel::Error | EntryLevel::Fatal | EntryLevel::Unknown => {
+                format!(
+                    r#"<span class="p" id="L{id}"{attributes}><span class="{class}"{style}>{prefix}{message}</span></span>"#
+                )
+            }
+            _ => format!(
+                r#"<span class="p" id="L{id}"{attributes}><span class="{class}"{style}>{prefix}</span>{message}</span>"#
+            ),
+        }
+    }
+}
 
-            if self.contain_log_level(line) {
-                let chunk = self.lines[last_idx_with_level..=previous_line_idx].join("\n");
+impl Parser {
+    pub fn new(lines: Vec<String>, custom_levels: Vec<CustomLevel>) -> Result<Self> {
+        if lines.is_empty() {
+            return Err(Error::EmptyLog);
+        }
 
-                let log_level = self.log_level(&chunk);
+        Ok(Self {
+            lines,
+            custom_levels,
+            date: None,
+        })
+    }
 
-                let entry_level = log_level.0;
-                let delimiter = log_level.1;
+    /// Date of the first line, for logs that only have the time of day in their timestamps.
+    ///
+    /// Use the date from the rotated file name, see [`LogFile::entries`](crate::log::set::LogFile::entries).
+    pub fn with_date(mut self, date: NaiveDate) -> Self {
+        self.date = Some(date);
+        self
+    }
 
-                let split = match entry_level {
-                    EntryLevel::Info | EntryLevel::Warn | EntryLevel::Error => {
-                        self.split(chunk, &delimiter.unwrap_or_default())
-                    }
-                    EntryLevel::Custom => {
-                        let delimiter: Vec<&String> = self
-                            .delimiters
-                            .custom
-                            .iter()
-                            .filter(|&x| chunk.contains(x))
-                            .collect();
+    /// Parses lines as they arrive, e.g. straight from a [`Log`](crate::log::Log).
+    ///
+    /// Only the entry being built is kept in memory, so arbitrarily large logs can be parsed.
+    pub fn stream<S>(lines: S, custom_levels: Vec<CustomLevel>) -> ParserStream<S>
+    where
+        S: Stream<Item = io::Result<String>> + Unpin,
+    {
+        ParserStream::new(lines, custom_levels)
+    }
 
-                        let delimiter = delimiter[0].as_str();
+    /// Blocking counterpart of [`Parser::stream`].
+    pub fn iter<I>(lines: I, custom_levels: Vec<CustomLevel>) -> ParserIter<I::IntoIter>
+    where
+        I: IntoIterator<Item = String>,
+    {
+        ParserIter::new(lines.into_iter(), custom_levels)
+    }
 
-                        let split = chunk.split_once(delimiter).unwrap();
+    pub fn entries(&self) -> Vec<LogEntry> {
+        let mut grouper = EntryGrouper::new(self.custom_levels.clone());
+        if let Some(date) = self.date {
+            grouper.set_date(date);
+        }
 
-                        (format!("{}{}", split.0, delimiter), split.1.to_string())
-                    }
-                    EntryLevel::Unknown => (String::new(), chunk),
-                };
+        let mut log_entries: Vec<LogEntry> = self
+            .lines
+            .iter()
+            .filter_map(|line| grouper.push(line.to_owned()))
+            .collect();
 
-                prefix = split.0;
-                message = split.1;
+        log_entries.extend(grouper.finish());
 
-                log_entries.push(LogEntry {
-                    log_level: last_log_level,
-                    prefix,
-                    message,
-                });
+        log_entries
+    }
 
-                last_idx_with_level = index;
-                last_log_level = self.log_level(line).0;
-            }
-        }
+    /// Which formats the log is written in, and where.
+    pub fn formats(&self) -> Vec<FormatSegment> {
+        format_segments(&self.entries())
+    }
 
-        match self.contain_log_level(&self.lines[last_line_idx]) {
-            false => {
-                let chunk = self.lines[last_idx_with_level..=last_line_idx].join("\n");
-                log_entries.push(LogEntry {
-                    log_level: last_log_level,
-                    message: chunk,
-                    prefix: String::new(),
-                })
-            }
-            true => {
-                let mut i_prefix = String::new();
-                let mut i_message = String::new();
-
-                let message = &self.lines[last_line_idx];
-
-                let log_level = self.log_level(message);
-
-                let entry_level = log_level.0;
-                let delimiter = log_level.1.unwrap_or_default();
-
-                match entry_level {
-                    EntryLevel::Info | EntryLevel::Warn | EntryLevel::Error => {
-                        let split = self.split(message.to_owned(), &delimiter);
-
-                        i_prefix = split.0;
-                        i_message = split.1;
-                    }
-                    EntryLevel::Custom => {
-                        let delimiter: Vec<&String> = self
-                            .delimiters
-                            .custom
-                            .iter()
-                            .filter(|&x| message.contains(x))
-                            .collect();
-                        let delimiter = delimiter[0].as_str();
-
-                        let split = self.split(message.to_owned(), delimiter);
-
-                        i_prefix = split.0;
-                        i_message = split.1;
-                    }
-                    EntryLevel::Unknown => {}
-                }
-
-                log_entries.push(LogEntry {
-                    log_level: entry_level,
-                    prefix: i_prefix,
-                    message: i_message,
-                });
-            }
-        }
+    /// Repeated exceptions, see [`ExceptionGroups`].
+    pub fn exception_groups(&self, frames: usize) -> Vec<ExceptionGroup> {
+        let mut groups = ExceptionGroups::new(frames);
+        groups.extend(self.entries());
+        groups.into_groups()
+    }
 
-        if self.contain_log_level(&self.lines[0]) {
-            log_entries.remove(0);
+    /// Lines of the log as plain text: entries of structured layouts are rendered like a text
+    /// log, other logs are returned as is.
+    pub fn text(&self) -> Vec<String> {
+        if !Layout::detect(&self.lines[0]).is_structured() {
+            return self.lines.clone();
         }
 
-        log_entries
+        self.entries()
+            .iter()
+            .flat_map(|entry| {
+                entry
+                    .to_string()
+                    .lines()
+                    .map(String::from)
+                    .collect::<Vec<_>>()
+            })
+            .collect()
     }
 
     pub fn get_chunks(&self) -> Vec<String> {
-        let chunks = self.parse();
+        let chunks = self.entries();
 
         let mut output = Vec::new();
 
         for chunk in chunks {
-            output.push(format!("{}{}", chunk.prefix, chunk.message))
+            output.push(chunk.to_string())
         }
 
         output
     }
 
     pub fn html(&self) -> Vec<String> {
-        let parts = self.parse();
+        let parts = self.entries();
 
         let mut html_parts = Vec::new();
 
-        let mut id: usize = 1;
-
-        for part in parts {
-            let html_part = match part.log_level {
-                EntryLevel::Info => format!(
-                    r#"<span class="p" id="L{id}"><span class={}>{}</span>{}</span>"#,
-                    part.log_level,
-                    html_escape::encode_text(part.prefix.as_str()),
-                    html_escape::encode_text(part.message.as_str())
-                ),
-                EntryLevel::Warn | EntryLevel::Error | EntryLevel::Unknown => format!(
-                    r#"<span class="p" id="L{id}"><span class="{}">{}{}</span></span>"#,
-                    part.log_level,
-                    html_escape::encode_text(part.prefix.as_str()),
-                    html_escape::encode_text(part.message.as_str()),
-                ),
-                EntryLevel::Custom => format!(
-                    r#"<span class="p" id="L{id}"><span class="{}">{}</span>{}</span>"#,
-                    part.log_level,
-                    html_escape::encode_text(part.prefix.as_str()),
-                    html_escape::encode_text(part.message.as_str())
-                ),
-            };
-            html_parts.push(html_part);
-            id += 1;
+        for (id, part) in (1usize..).zip(parts) {
+            html_parts.push(part.html(id, &self.custom_levels));
         }
 
         html_parts
     }
 
-    fn log_level(&self, line: &str) -> (EntryLevel, Option<String>) {
-        if let Some(del) = self.delimiters.info.iter().find(|&del| line.contains(del)) {
-            return (EntryLevel::Info, Some(del.to_owned()));
+    /// Like [`Parser::html`], but only with the entries selected by `filter`.
+    ///
+    /// Entries keep their `L{id}` anchors from the unfiltered output.
+    pub fn html_filtered(&self, filter: &EntryFilter) -> Vec<String> {
+        (1usize..)
+            .zip(self.entries())
+            .filter(|(_, part)| filter.matches(part))
+            .map(|(id, part)| part.html(id, &self.custom_levels))
+            .collect()
+    }
+}
+
+/// Levels entries of structured layouts that were parsed without `custom_levels`.
+pub(crate) fn apply_custom_levels(entries: &mut [LogEntry], custom_levels: &[CustomLevel]) {
+    if custom_levels.is_empty() {
+        return;
+    }
+
+    let grammar = LevelGrammar::new(custom_levels);
+
+    for entry in entries
+        .iter_mut()
+        .filter(|entry| entry.log_level == EntryLevel::Unknown)
+    {
+        let Some(level) = &entry.level_token else {
+            continue;
+        };
+
+        let (log_level, custom_level) = grammar.token_level(&grammar.record_token(level));
+
+        entry.log_level = log_level;
+        entry.custom_level = custom_level.map(|custom| custom.name.clone());
+        entry.severity = custom_level.map_or(log_level, |custom| custom.severity);
+    }
+}
+
+/// Groups lines into entries: a line with a log level starts a new entry, lines without one
+/// (stack traces, multi-line messages) are appended to the current entry.
+///
+/// The level is detected per line with a [`LevelGrammar`], so logs mixing formats (a wrapper's
+/// `2024-01-12 12:34:56 INFO:` or bare `INFO: ` lines between vanilla `[INFO]:` ones) are split
+/// correctly.
+///
+/// Logs written with a structured layout (Log4j2's `JsonLayout` or `XmlLayout`) are detected
+/// from their first line instead, each record is one entry.
+///
+/// An entry is cut once it reaches `MAX_ENTRY_LINES` lines or `MAX_ENTRY_BYTES` bytes, the
+/// lines after it start a new entry without a level.
+pub(crate) struct EntryGrouper {
+    grammar: LevelGrammar,
+    /// Detected from the first line
+    layout: Option<Layout>,
+    record: RecordScanner,
+    lines: Vec<String>,
+    dates: Option<DateTracker>,
+    /// Level of the current entry, found when its level line was pushed
+    header: Option<HeaderLevel>,
+    /// Line number and offset in the decoded text of the next pushed line
+    next_line: usize,
+    next_offset: usize,
+    /// Line number and offset in the decoded text of the current entry
+    start_line: usize,
+    text_start: usize,
+}
+
+impl EntryGrouper {
+    pub(crate) fn new(custom_levels: Vec<CustomLevel>) -> Self {
+        Self {
+            grammar: LevelGrammar::new(&custom_levels),
+            layout: None,
+            record: RecordScanner::default(),
+            lines: Vec::new(),
+            dates: None,
+            header: None,
+            next_line: 1,
+            next_offset: 0,
+            start_line: 1,
+            text_start: 0,
         }
+    }
 
-        let warn_delimiter = self.delimiters.warn.iter().find(|&del| line.contains(del));
+    pub(crate) fn set_date(&mut self, date: NaiveDate) {
+        self.dates = Some(DateTracker::new(date));
+    }
+
+    /// Returns the previous entry once `line` starts a new one.
+    pub(crate) fn push(&mut self, line: String) -> Option<LogEntry> {
+        let layout = *self.layout.get_or_insert_with(|| Layout::detect(&line));
 
-        if let Some(del) = warn_delimiter {
-            return (EntryLevel::Warn, Some(del.to_owned()));
+        if layout.is_structured() {
+            return self.push_record(layout, line);
         }
 
-        let error_delimiter = self.delimiters.error.iter().find(|&del| line.contains(del));
+        // The level line belongs to the JUL header before it
+        let level_line = self.lines.len() == 1 && is_jul_header(&self.lines[0]);
+
+        let header = self.grammar.level(&line);
+        let starts_entry = is_jul_header(&line) || (!level_line && header.is_some());
 
-        if let Some(del) = error_delimiter {
-            return (EntryLevel::Error, Some(del.to_owned()));
+        let completed = match starts_entry || self.is_full() {
+            true => self.finish(),
+            false => None,
+        };
+
+        if self.lines.is_empty() {
+            self.start_line = self.next_line;
+            self.text_start = self.next_offset;
         }
 
-        let custom_delimiter = self
-            .delimiters
-            .custom
-            .iter()
-            .find(|&del| line.contains(del));
-        if let Some(del) = custom_delimiter {
-            return (EntryLevel::Custom, Some(del.to_owned()));
+        // Detected once, later lines can't change the level of an entry
+        if self.lines.is_empty() || level_line {
+            self.header = header;
         }
 
-        (EntryLevel::Unknown, None)
+        self.next_line += 1;
+        self.next_offset += line.len() + 1;
+
+        self.lines.push(line);
+
+        completed
+    }
+
+    /// Returns the record's entry once `line` completes it, lines between records are skipped.
+    fn push_record(&mut self, layout: Layout, line: String) -> Option<LogEntry> {
+        if self.lines.is_empty() {
+            if layout.is_framing(&line) {
+                self.next_line += 1;
+                self.next_offset += line.len() + 1;
+                return None;
+            }
+
+            self.start_line = self.next_line;
+            self.text_start = self.next_offset;
+            self.record = RecordScanner::default();
+        }
+
+        self.next_line += 1;
+        self.next_offset += line.len() + 1;
+
+        let complete = self.record.feed(layout, &line);
+        self.lines.push(line);
+
+        match complete || self.is_full() {
+            true => self.finish(),
+            false => None,
+        }
+    }
+
+    fn is_full(&self) -> bool {
+        self.lines.len() >= MAX_ENTRY_LINES || self.next_offset - self.text_start >= MAX_ENTRY_BYTES
+    }
+
+    pub(crate) fn finish(&mut self) -> Option<LogEntry> {
+        if self.lines.is_empty() {
+            return None;
+        }
+
+        // Records that can't be parsed are kept as text
+        if let Some(layout) = self.layout.filter(Layout::is_structured) {
+            if let Some(entry) = self.finish_record(layout) {
+                return Some(entry);
+            }
+        }
+
+        let lines = std::mem::take(&mut self.lines);
+        let header = self.header.take();
+
+        // Offset of the line with the level in the entry
+        let level_offset = match lines.len() > 1 && is_jul_header(&lines[0]) {
+            true => lines[0].len() + 1,
+            false => 0,
+        };
+
+        let timestamp = Timestamp::parse(&lines[0]).map(|timestamp| match &mut self.dates {
+            Some(dates) => dates.complete(timestamp),
+            None => timestamp,
+        });
+
+        let mut chunk = lines.join("\n");
+
+        let end_line = self.start_line + lines.len() - 1;
+        let text_end = self.text_start + chunk.len();
+
+        let (prefix, message) = match &header {
+            Some(header) => {
+                let message = chunk.split_off(level_offset + header.end);
+                (chunk, message)
+            }
+            None => (String::new(), chunk),
+        };
+
+        let (thread, logger) = thread_and_logger(&lines[0], prefix.len());
+
+        let stack_trace = StackTrace::parse(&message.lines().collect::<Vec<_>>());
+
+        Some(LogEntry {
+            log_level: header
+                .as_ref()
+                .map_or(EntryLevel::Unknown, |header| header.level),
+            level_token: header.as_ref().map(|header| header.token.clone()),
+            custom_level: header
+                .as_ref()
+                .and_then(|header| header.custom_level.clone()),
+            severity: header
+                .as_ref()
+                .map_or(EntryLevel::Unknown, |header| header.severity),
+            prefix,
+            message,
+            timestamp,
+            thread,
+            logger,
+            format: header.map(|header| header.format),
+            stack_trace,
+            start_line: self.start_line,
+            end_line,
+            text_start: self.text_start,
+            text_end,
+        })
+    }
+
+    /// Entry of a structured record, rendered like Forge's text layout:
+    /// `[12:34:56] [main/INFO] [logger]: message`, followed by the thrown exception.
+    fn finish_record(&mut self, layout: Layout) -> Option<LogEntry> {
+        let record = self.lines.join("\n");
+        let event = Event::parse(layout, &record)?;
+
+        let end_line = self.start_line + self.lines.len() - 1;
+        self.lines.clear();
+
+        let token = self.grammar.record_token(&event.level);
+        let (log_level, custom_level) = self.grammar.token_level(&token);
+
+        let mut prefix = match &event.timestamp {
+            Some(timestamp) => format!("[{}] ", timestamp.time().format("%H:%M:%S")),
+            None => String::new(),
+        };
+
+        match &event.thread {
+            Some(thread) => prefix.push_str(&format!("[{thread}/{token}]")),
+            None => prefix.push_str(&format!("[{token}]")),
+        }
+
+        let mut message = match &event.logger {
+            Some(logger) => format!(" [{logger}]: {}", event.message),
+            None => {
+                prefix.push(':');
+                format!(" {}", event.message)
+            }
+        };
+
+        if let Some(thrown) = &event.thrown {
+            message.push('\n');
+            message.push_str(thrown);
+        }
+
+        let stack_trace = StackTrace::parse(&message.lines().collect::<Vec<_>>());
+
+        Some(LogEntry {
+            log_level,
+            custom_level: custom_level.map(|custom| custom.name.clone()),
+            severity: custom_level.map_or(log_level, |custom| custom.severity),
+            level_token: Some(event.level),
+            prefix,
+            message,
+            timestamp: event.timestamp,
+            thread: event.thread,
+            logger: event.logger,
+            format: None,
+            stack_trace,
+            start_line: self.start_line,
+            end_line,
+            text_start: self.text_start,
+            text_end: self.text_start + record.len(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entries(log: &str) -> Vec<LogEntry> {
+        Parser::new(log.lines().map(String::from).collect(), Vec::new())
+            .unwrap()
+            .entries()
+    }
+
+    #[test]
+    fn continuation_lines_stay_in_their_entry() {
+        let entries = entries(
+            "[12Jan2024 12:34:56.789] [main/WARN] [net.minecraftforge.Foo/]: First line\n    \
+             WARN continuation\n\
+             [12:34:57] [Server thread/INFO]: Multi-line message\n\
+             INFO continuation line\n\
+             [12:34:58] [Server thread/INFO]: Done",
+        );
+
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].log_level, EntryLevel::Warn);
+        assert!(entries[0].message.ends_with("    WARN continuation"));
+        assert!(entries[1].message.ends_with("INFO continuation line"));
+        assert_eq!(entries[1].end_line, 4);
+    }
+
+    #[test]
+    fn bare_levels_split_entries() {
+        let entries = entries("INFO: Starting\nWARN: Low memory\n  details");
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[1].log_level, EntryLevel::Warn);
+        assert_eq!(entries[1].message, " Low memory\n  details");
+    }
+
+    #[test]
+    fn bare_level_before_bracketed_headers() {
+        let entries = entries(
+            "INFO: Starting wrapper\n\
+             [12:34:56] [Server thread/INFO]: Starting minecraft server",
+        );
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].log_level, EntryLevel::Info);
+        assert_eq!(entries[0].message, " Starting wrapper");
+        assert_eq!(entries[1].log_level, EntryLevel::Info);
+        assert_eq!(entries[1].thread.as_deref(), Some("Server thread"));
+    }
+
+    #[test]
+    fn wrapper_lines_between_vanilla_lines() {
+        let entries = entries(
+            "[12:34:56] [Server thread/INFO]: Starting minecraft server version 1.20.4\n\
+             INFO: wrapper says hi\n\
+             [12:34:57] [Server thread/WARN]: Can't keep up!\n\
+             \tat net.minecraft.Foo.bar(Foo.java:1)\n\
+             WARN: wrapper restarting soon\n\
+             [12:34:58] [Server thread/INFO]: Done",
+        );
+
+        let spans: Vec<_> = entries
+            .iter()
+            .map(|entry| (entry.start_line, entry.end_line, entry.log_level))
+            .collect();
+        assert_eq!(
+            spans,
+            [
+                (1, 1, EntryLevel::Info),
+                (2, 2, EntryLevel::Info),
+                (3, 4, EntryLevel::Warn),
+                (5, 5, EntryLevel::Warn),
+                (6, 6, EntryLevel::Info),
+            ]
+        );
+        assert_eq!(entries[1].thread, None);
+        assert_eq!(entries[1].message, " wrapper says hi");
+    }
+
+    #[test]
+    fn entries_are_capped() {
+        let lines = (0..MAX_ENTRY_LINES + 10).map(|idx| format!("line {idx}"));
+        let entries: Vec<_> = Parser::iter(lines, Vec::new()).collect();
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].end_line, MAX_ENTRY_LINES);
+        assert_eq!(entries[1].start_line, MAX_ENTRY_LINES + 1);
+        assert_eq!(entries[1].log_level, EntryLevel::Unknown);
+
+        let line = "x".repeat(1024 * 1024);
+        let entries = Parser::new(vec![line; 6], Vec::new()).unwrap().entries();
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].end_line, 4);
+    }
+
+    #[test]
+    fn custom_levels_of_structured_entries() {
+        let mut entries =
+            entries(r#"{"thread":"main","level":"AUDIT","loggerName":"Audit","message":"Login"}"#);
+        assert_eq!(entries[0].log_level, EntryLevel::Unknown);
+
+        apply_custom_levels(&mut entries, &[CustomLevel::from("AUDIT")]);
+
+        assert_eq!(entries[0].log_level, EntryLevel::Custom);
+        assert_eq!(entries[0].custom_level.as_deref(), Some("audit"));
+        assert_eq!(entries[0].severity, EntryLevel::Info);
     }
 
-    fn contain_log_level(&self, line: &str) -> bool {
-        !matches!(self.log_level(line).0, EntryLevel::Unknown)
+    #[test]
+    fn jul_entries() {
+        let entries = entries(
+            "Jan 12, 2024 12:34:56 PM org.bukkit.Foo bar\n\
+             WARNING: Could not load\n\
+             Jan 12, 2024 12:34:57 PM org.bukkit.Foo bar\n\
+             INFO: Loaded",
+        );
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].log_level, EntryLevel::Warn);
+        assert_eq!(entries[1].log_level, EntryLevel::Info);
     }
 }
@@ -0,0 +1,362 @@
+use regex::Regex;
+use serde::Serialize;
+use std::sync::LazyLock;
+
+// java.lang.IllegalStateException: message, Exception in thread "main" java.lang.Error
+static EXCEPTION_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r#"^\s*(?:Exception in thread "[^"]*"\s+)?((?:[a-zA-Z_$][\w$]*\.)+[A-Z][\w$]*)(?::\s?(.*))?$"#,
+    )
+    .unwrap_or_else(|e| panic!("Failed to create 'EXCEPTION_REGEX': {}", e))
+});
+
+// Caused by: java.io.IOException: message, Suppressed: ...
+static NESTED_EXCEPTION_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(\s*)(Caused by|Suppressed): ((?:[a-zA-Z_$][\w$]*\.)*[\w$]+)(?::\s?(.*))?$")
+        .unwrap_or_else(|e| panic!("Failed to create 'NESTED_EXCEPTION_REGEX': {}", e))
+});
+
+// at app//org.bukkit.Foo.bar(Foo.java:12) ~[paper-1.20.4.jar:git-Paper-496]
+// Hidden classes keep their slash: at Foo$$Lambda$12/0x0000000800c0b000.run(Unknown Source)
+static FRAME_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"^\s*at\s+(?:(\S*?)/)??((?:[^\s(/]|/0x)+)\.([^\s.(/]+)\(([^)]*)\)(?:\s*~?\[([^\]]*)\])?",
+    )
+    .unwrap_or_else(|e| panic!("Failed to create 'FRAME_REGEX': {}", e))
+});
+
+// ... 12 more
+static ELIDED_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^\s*\.\.\. (\d+) more")
+        .unwrap_or_else(|e| panic!("Failed to create 'ELIDED_REGEX': {}", e))
+});
+
+#[derive(Serialize, Clone, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct StackFrame {
+    pub class: String,
+    pub method: String,
+    pub file: Option<String>,
+    pub line: Option<u32>,
+    /// Java module, with version when known, e.g. `java.base` or `minecraft@1.20.1`
+    pub module: Option<String>,
+    /// Class loader before the module, e.g. `app` or Forge's `TRANSFORMER`
+    pub class_loader: Option<String>,
+    /// Jar the class was loaded from, as Log4j prints it after the frame
+    pub jar: Option<String>,
+}
+
+impl StackFrame {
+    pub(crate) fn parse(line: &str) -> Option<Self> {
+        let captures = FRAME_REGEX.captures(line)?;
+
+        let (class_loader, module) = match captures.get(1).map(|m| m.as_str()) {
+            // loader/module@version/, loader//
+            Some(path) => match path.split_once('/') {
+                Some((loader, module)) => (non_empty(loader), non_empty(module)),
+                None => (None, non_empty(path)),
+            },
+            None => (None, None),
+        };
+
+        let source = captures.get(4)?.as_str();
+
+        let (file, line) = match source.rsplit_once(':') {
+            Some((file, line)) if line.parse::<u32>().is_ok() => {
+                (non_empty(file), line.parse().ok())
+            }
+            // Native Method, Unknown Source
+            _ => (
+                Some(source).filter(|s| s.contains('.')).map(String::from),
+                None,
+            ),
+        };
+
+        Some(Self {
+            class: captures.get(2)?.as_str().to_string(),
+            method: captures.get(3)?.as_str().to_string(),
+            file,
+            line,
+            module,
+            class_loader,
+            jar: captures.get(5).and_then(|m| non_empty(m.as_str())),
+        })
+    }
+}
+
+/// Formatted like Java prints it, without the leading tab and `at`.
+impl std::fmt::Display for StackFrame {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (&self.class_loader, &self.module) {
+            (Some(loader), Some(module)) => write!(f, "{loader}/{module}/")?,
+            (Some(loader), None) => write!(f, "{loader}//")?,
+            (None, Some(module)) => write!(f, "{module}/")?,
+            (None, None) => {}
+        }
+
+        write!(f, "{}.{}(", self.class, self.method)?;
+
+        match (&self.file, self.line) {
+            (Some(file), Some(line)) => write!(f, "{file}:{line})")?,
+            (Some(file), None) => write!(f, "{file})")?,
+            (None, _) => write!(f, "Unknown Source)")?,
+        }
+
+        match &self.jar {
+            Some(jar) => write!(f, " ~[{jar}]"),
+            None => Ok(()),
+        }
+    }
+}
+
+fn non_empty(s: &str) -> Option<String> {
+    Some(s).filter(|s| !s.is_empty()).map(String::from)
+}
+
+/// A Java exception with its frames, cause chain and suppressed exceptions.
+#[derive(Serialize, Clone, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct StackTrace {
+    pub class: String,
+    pub message: Option<String>,
+    pub frames: Vec<StackFrame>,
+    /// Frames shared with the enclosing exception, printed as `... N more`
+    pub elided_frames: usize,
+    pub cause: Option<Box<StackTrace>>,
+    pub suppressed: Vec<StackTrace>,
+}
+
+impl StackTrace {
+    pub(crate) fn new(class: &str, message: Option<&str>) -> Self {
+        Self {
+            class: class.to_string(),
+            message: message.and_then(non_empty),
+            frames: Vec::new(),
+            elided_frames: 0,
+            cause: None,
+            suppressed: Vec::new(),
+        }
+    }
+
+    /// Class name without the package, e.g. `NullPointerException`.
+    pub fn simple_class(&self) -> &str {
+        self.class.rsplit('.').next().unwrap_or(&self.class)
+    }
+
+    /// Whether this is `class`, given either fully qualified or without the package.
+    pub fn is(&self, class: &str) -> bool {
+        match class.contains('.') {
+            true => self.class == class,
+            false => self.simple_class() == class,
+        }
+    }
+
+    /// This exception followed by its causes.
+    pub fn causes(&self) -> impl Iterator<Item = &StackTrace> {
+        std::iter::successors(Some(self), |trace| trace.cause.as_deref())
+    }
+
+    /// The innermost cause, usually where the problem actually started.
+    pub fn root_cause(&self) -> &StackTrace {
+        self.causes().last().unwrap_or(self)
+    }
+
+    /// Every exception in the trace: causes, suppressed exceptions and their causes.
+    pub fn all(&self) -> Vec<&StackTrace> {
+        let mut all = Vec::new();
+
+        for trace in self.causes() {
+            all.push(trace);
+
+            for suppressed in &trace.suppressed {
+                all.extend(suppressed.all());
+            }
+        }
+
+        all
+    }
+
+    /// Parses the first stack trace in an entry's lines.
+    ///
+    /// The exception line is the last one naming an exception class before the first `at`
+    /// frame, lines after the trace ends are ignored.
+    pub fn parse<S: AsRef<str>>(lines: &[S]) -> Option<Self> {
+        let first_frame = lines
+            .iter()
+            .position(|line| FRAME_REGEX.is_match(line.as_ref()))?;
+
+        // Messages can span several lines
+        let header = (0..first_frame)
+            .rev()
+            .find(|&idx| EXCEPTION_REGEX.is_match(lines[idx].as_ref()))?;
+        let captures = EXCEPTION_REGEX.captures(lines[header].as_ref())?;
+
+        let mut message: Vec<&str> = captures.get(2).map(|m| m.as_str()).into_iter().collect();
+        message.extend(
+            lines[header + 1..first_frame]
+                .iter()
+                .map(|line| line.as_ref()),
+        );
+        let message = message.join("\n");
+
+        let root = StackTrace::new(captures.get(1)?.as_str(), Some(&message));
+
+        let mut builder = Builder::new(root);
+        // Between a `Caused by:` line and its first frame, where its message may continue
+        let mut in_message = false;
+
+        for line in &lines[first_frame..] {
+            let line = line.as_ref();
+
+            if let Some(frame) = StackFrame::parse(line) {
+                builder.current().frames.push(frame);
+                in_message = false;
+            } else if let Some(captures) = ELIDED_REGEX.captures(line) {
+                builder.current().elided_frames = captures[1].parse().unwrap_or(0);
+                in_message = false;
+            } else if let Some(captures) = NESTED_EXCEPTION_REGEX.captures(line) {
+                let trace = StackTrace::new(&captures[3], captures.get(4).map(|m| m.as_str()));
+                let depth = indentation(&captures[1]);
+
+                match &captures[2] {
+                    "Caused by" => builder.cause(depth, trace),
+                    _ => builder.suppressed(depth, trace),
+                }
+                in_message = true;
+            } else if in_message {
+                let message = builder.current().message.get_or_insert_with(String::new);
+                message.push('\n');
+                message.push_str(line);
+            } else {
+                break;
+            }
+        }
+
+        Some(builder.build())
+    }
+}
+
+/// Formatted like `Throwable::printStackTrace`, so it can be parsed back with
+/// [`StackTrace::parse`].
+impl std::fmt::Display for StackTrace {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut lines = Vec::new();
+        self.write_lines("", "", &mut lines);
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+impl StackTrace {
+    fn write_lines(&self, indent: &str, label: &str, lines: &mut Vec<String>) {
+        match &self.message {
+            Some(message) => lines.push(format!("{indent}{label}{}: {message}", self.class)),
+            None => lines.push(format!("{indent}{label}{}", self.class)),
+        }
+
+        for frame in &self.frames {
+            lines.push(format!("{indent}\tat {frame}"));
+        }
+
+        if self.elided_frames > 0 {
+            lines.push(format!("{indent}\t... {} more", self.elided_frames));
+        }
+
+        for suppressed in &self.suppressed {
+            suppressed.write_lines(&format!("{indent}\t"), "Suppressed: ", lines);
+        }
+
+        if let Some(cause) = &self.cause {
+            cause.write_lines(indent, "Caused by: ", lines);
+        }
+    }
+}
+
+/// Nesting of a `Caused by:`/`Suppressed:` line, one level per tab (or 4 spaces when tabs
+/// were converted).
+fn indentation(whitespace: &str) -> usize {
+    let tabs = whitespace.chars().filter(|&c| c == '\t').count();
+    let spaces = whitespace.chars().filter(|&c| c == ' ').count();
+    tabs + spaces / 4
+}
+
+/// Links exceptions by their indentation, `Suppressed:` blocks are indented one level deeper
+/// than the exception they belong to and their causes are printed at the same level.
+struct Builder {
+    traces: Vec<Node>,
+    /// Last exception of the cause chain at each level
+    chains: Vec<usize>,
+    current: usize,
+}
+
+struct Node {
+    trace: StackTrace,
+    cause: Option<usize>,
+    suppressed: Vec<usize>,
+}
+
+impl Builder {
+    fn new(root: StackTrace) -> Self {
+        Self {
+            traces: vec![Node {
+                trace: root,
+                cause: None,
+                suppressed: Vec::new(),
+            }],
+            chains: vec![0],
+            current: 0,
+        }
+    }
+
+    fn current(&mut self) -> &mut StackTrace {
+        &mut self.traces[self.current].trace
+    }
+
+    fn push(&mut self, trace: StackTrace) -> usize {
+        self.traces.push(Node {
+            trace,
+            cause: None,
+            suppressed: Vec::new(),
+        });
+        self.current = self.traces.len() - 1;
+        self.current
+    }
+
+    fn cause(&mut self, depth: usize, trace: StackTrace) {
+        let depth = depth.min(self.chains.len() - 1);
+        let parent = self.chains[depth];
+
+        let idx = self.push(trace);
+        self.traces[parent].cause = Some(idx);
+
+        self.chains.truncate(depth);
+        self.chains.push(idx);
+    }
+
+    fn suppressed(&mut self, depth: usize, trace: StackTrace) {
+        let depth = depth.clamp(1, self.chains.len());
+        let parent = self.chains[depth - 1];
+
+        let idx = self.push(trace);
+        self.traces[parent].suppressed.push(idx);
+
+        self.chains.truncate(depth);
+        self.chains.push(idx);
+    }
+
+    fn build(mut self) -> StackTrace {
+        self.take(0)
+    }
+
+    fn take(&mut self, idx: usize) -> StackTrace {
+        let cause = self.traces[idx].cause;
+        let suppressed = std::mem::take(&mut self.traces[idx].suppressed);
+
+        let mut trace = StackTrace::new("", None);
+        std::mem::swap(&mut trace, &mut self.traces[idx].trace);
+
+        trace.cause = cause.map(|cause| Box::new(self.take(cause)));
+        trace.suppressed = suppressed.into_iter().map(|idx| self.take(idx)).collect();
+
+        trace
+    }
+}
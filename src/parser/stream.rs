@@ -0,0 +1,110 @@
+use super::{
+    log_level::CustomLevel,
+    parser::{EntryGrouper, LogEntry},
+};
+use crate::Result;
+use chrono::NaiveDate;
+use std::{
+    io,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use tokio_stream::Stream;
+
+pub struct ParserStream<S> {
+    lines: S,
+    grouper: EntryGrouper,
+    done: bool,
+}
+
+impl<S> ParserStream<S>
+where
+    S: Stream<Item = io::Result<String>> + Unpin,
+{
+    pub(crate) fn new(lines: S, custom_levels: Vec<CustomLevel>) -> Self {
+        Self {
+            lines,
+            grouper: EntryGrouper::new(custom_levels),
+            done: false,
+        }
+    }
+
+    /// See [`Parser::with_date`](super::parser::Parser::with_date).
+    pub fn with_date(mut self, date: NaiveDate) -> Self {
+        self.grouper.set_date(date);
+        self
+    }
+}
+
+impl<S> Stream for ParserStream<S>
+where
+    S: Stream<Item = io::Result<String>> + Unpin,
+{
+    type Item = Result<LogEntry>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        while !this.done {
+            match Pin::new(&mut this.lines).poll_next(cx) {
+                Poll::Ready(Some(Ok(line))) => {
+                    if let Some(entry) = this.grouper.push(line) {
+                        return Poll::Ready(Some(Ok(entry)));
+                    }
+                }
+                Poll::Ready(Some(Err(e))) => return Poll::Ready(Some(Err(e.into()))),
+                Poll::Ready(None) => {
+                    this.done = true;
+                    return Poll::Ready(this.grouper.finish().map(Ok));
+                }
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+
+        Poll::Ready(None)
+    }
+}
+
+pub struct ParserIter<I> {
+    lines: I,
+    grouper: EntryGrouper,
+    done: bool,
+}
+
+impl<I: Iterator<Item = String>> ParserIter<I> {
+    pub(crate) fn new(lines: I, custom_levels: Vec<CustomLevel>) -> Self {
+        Self {
+            lines,
+            grouper: EntryGrouper::new(custom_levels),
+            done: false,
+        }
+    }
+
+    /// See [`Parser::with_date`](super::parser::Parser::with_date).
+    pub fn with_date(mut self, date: NaiveDate) -> Self {
+        self.grouper.set_date(date);
+        self
+    }
+}
+
+impl<I: Iterator<Item = String>> Iterator for ParserIter<I> {
+    type Item = LogEntry;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.done {
+            match self.lines.next() {
+                Some(line) => {
+                    if let Some(entry) = self.grouper.push(line) {
+                        return Some(entry);
+                    }
+                }
+                None => {
+                    self.done = true;
+                    return self.grouper.finish();
+                }
+            }
+        }
+
+        None
+    }
+}
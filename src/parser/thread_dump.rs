@@ -0,0 +1,192 @@
+use super::{parser::LogEntry, stacktrace::StackFrame};
+use regex::Regex;
+use serde::Serialize;
+use std::sync::LazyLock;
+
+const STOPPED_RESPONDING: &str = "The server has stopped responding!";
+// Paper's early warning, printed every few seconds before the watchdog gives up
+const NOT_RESPONDED: &str = "The server has not responded for";
+const SERVER_THREAD_DUMP: &str = "Server thread dump";
+const ENTIRE_THREAD_DUMP: &str = "Entire Thread Dump";
+const SERVER_THREAD: &str = "Server thread";
+
+// Spigot version: git-Spigot-79a30d7-acbc348 (MC: 1.12.2), Paper version: git-Paper-196 (MC: 1.20.1)
+static VERSION_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^\w+ version: (.+)$")
+        .unwrap_or_else(|e| panic!("Failed to create 'VERSION_REGEX': {}", e))
+});
+
+// --- DO NOT REPORT THIS TO PAPER - THIS IS NOT A BUG OR A CRASH  - git-Paper-196 (MC: 1.20.1) ---
+// Printed right before an early warning, which has no version line of its own
+static EARLY_WARNING_VERSION_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^--- DO NOT REPORT THIS TO PAPER - THIS IS NOT A BUG OR A CRASH\s+- (.+) ---$")
+        .unwrap_or_else(|e| panic!("Failed to create 'EARLY_WARNING_VERSION_REGEX': {}", e))
+});
+
+// PID: 22 | Suspended: false | Native: false | State: RUNNABLE
+static THREAD_INFO_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^PID: (\d+) \| Suspended: (\w+) \| Native: (\w+) \| State: (\w+)")
+        .unwrap_or_else(|e| panic!("Failed to create 'THREAD_INFO_REGEX': {}", e))
+});
+
+/// A thread of a watchdog thread dump.
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DumpedThread {
+    pub name: String,
+    pub id: Option<u64>,
+    /// e.g. `RUNNABLE`, `WAITING` or `BLOCKED`
+    pub state: Option<String>,
+    pub suspended: bool,
+    /// Whether the thread was in native code, e.g. reading a socket
+    pub native: bool,
+    /// `Locked on:` frames of the monitors the thread holds
+    pub locked_monitors: Vec<String>,
+    /// Top of the stack first
+    pub frames: Vec<StackFrame>,
+    /// 1-based line of the first frame, each frame is on its own line
+    pub stack_line: usize,
+}
+
+impl DumpedThread {
+    fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            id: None,
+            state: None,
+            suspended: false,
+            native: false,
+            locked_monitors: Vec::new(),
+            frames: Vec::new(),
+            stack_line: 0,
+        }
+    }
+}
+
+/// A thread dump printed by the Spigot or Paper watchdog when the server stops ticking.
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ThreadDump {
+    /// Paper's `The server has not responded for 10 seconds!` warning, the server may
+    /// still recover
+    pub early_warning: bool,
+    /// e.g. `git-Paper-196 (MC: 1.20.1)`
+    pub version: Option<String>,
+    /// The `Server thread dump` section, only the main thread
+    pub server_threads: Vec<DumpedThread>,
+    /// The `Entire Thread Dump` section, absent from early warnings
+    pub threads: Vec<DumpedThread>,
+    /// 1-based number of the dump's first line in the log
+    pub start_line: usize,
+}
+
+#[derive(PartialEq)]
+enum Section {
+    Head,
+    ServerThread,
+    Entire,
+}
+
+impl ThreadDump {
+    /// The main `Server thread`, whose stack shows what the server was stuck on.
+    pub fn server_thread(&self) -> Option<&DumpedThread> {
+        self.server_threads
+            .iter()
+            .chain(&self.threads)
+            .find(|thread| thread.name == SERVER_THREAD)
+    }
+
+    /// Parses the watchdog thread dumps in the log, in order.
+    pub fn find_all(entries: &[LogEntry]) -> Vec<Self> {
+        let mut dumps = Vec::new();
+        let mut dump: Option<(Self, Section, Option<&str>)> = None;
+        // Version of the entry right before, for early warnings
+        let mut warning_version = None;
+
+        for entry in entries {
+            let message = entry.message.trim_start_matches(':').trim();
+            let previous_version = warning_version.take();
+
+            if let Some(captures) = EARLY_WARNING_VERSION_REGEX.captures(message) {
+                warning_version = Some(captures[1].to_string());
+            }
+
+            if message.starts_with(STOPPED_RESPONDING) || message.starts_with(NOT_RESPONDED) {
+                dumps.extend(dump.take().map(|(dump, ..)| dump));
+
+                let early_warning = message.starts_with(NOT_RESPONDED);
+                dump = Some((
+                    Self {
+                        early_warning,
+                        version: previous_version.filter(|_| early_warning),
+                        server_threads: Vec::new(),
+                        threads: Vec::new(),
+                        start_line: entry.start_line,
+                    },
+                    Section::Head,
+                    entry.thread.as_deref(),
+                ));
+                continue;
+            }
+
+            let Some((current, section, watchdog)) = dump.as_mut() else {
+                continue;
+            };
+
+            // The dump is over once another thread logs
+            if entry.thread.is_some() && entry.thread.as_deref() != *watchdog {
+                dumps.extend(dump.take().map(|(dump, ..)| dump));
+                continue;
+            }
+
+            if message.starts_with(SERVER_THREAD_DUMP) {
+                *section = Section::ServerThread;
+                continue;
+            }
+
+            if message.starts_with(ENTIRE_THREAD_DUMP) {
+                *section = Section::Entire;
+                continue;
+            }
+
+            if *section == Section::Head {
+                if let Some(captures) = VERSION_REGEX.captures(message) {
+                    current.version = Some(captures[1].to_string());
+                }
+                continue;
+            }
+
+            let threads = match section {
+                Section::ServerThread => &mut current.server_threads,
+                _ => &mut current.threads,
+            };
+
+            if let Some(name) = message.strip_prefix("Current Thread:") {
+                threads.push(DumpedThread::new(name.trim()));
+                continue;
+            }
+
+            let Some(thread) = threads.last_mut() else {
+                continue;
+            };
+
+            if let Some(captures) = THREAD_INFO_REGEX.captures(message) {
+                thread.id = captures[1].parse().ok();
+                thread.suspended = &captures[2] == "true";
+                thread.native = &captures[3] == "true";
+                thread.state = Some(captures[4].to_string());
+            } else if let Some(monitor) = message.strip_prefix("Locked on:") {
+                thread.locked_monitors.push(monitor.trim().to_string());
+            } else if let Some(frame) = StackFrame::parse(&format!("at {message}")) {
+                // Frames are printed without `at`
+                if thread.frames.is_empty() {
+                    thread.stack_line = entry.start_line;
+                }
+                thread.frames.push(frame);
+            }
+        }
+
+        dumps.extend(dump.map(|(dump, ..)| dump));
+        dumps
+    }
+}
@@ -0,0 +1,173 @@
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta};
+use regex::{Captures, Regex};
+use serde::Serialize;
+use std::sync::LazyLock;
+
+// `docker logs --timestamps`: 2024-01-12T12:34:56.123456789Z [12:34:56 INFO]: ...
+static CONTAINER_TIMESTAMP_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(\d{4}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2}(?:\.\d+)?(?:Z|[+-]\d{2}:?\d{2}))\s+")
+        .unwrap_or_else(|e| panic!("Failed to create 'CONTAINER_TIMESTAMP_REGEX': {}", e))
+});
+
+// docker compose: mc_1  | [12:34:56 INFO]: ...
+static CONTAINER_NAME_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^[\w.-]+\s+\|\s?")
+        .unwrap_or_else(|e| panic!("Failed to create 'CONTAINER_NAME_REGEX': {}", e))
+});
+
+// Paper/Log4j: [2024-01-12 12:34:56], 2024-01-12 12:34:56,789
+static DATE_TIME_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^\[?(\d{4}-\d{2}-\d{2})[ T](\d{2}:\d{2}:\d{2})(?:[.,](\d{1,9}))?")
+        .unwrap_or_else(|e| panic!("Failed to create 'DATE_TIME_REGEX': {}", e))
+});
+
+// Forge: [12Jan2024 12:34:56.789]
+static FORGE_DATE_TIME_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^\[?(\d{1,2}[A-Za-z]{3}\d{4}) (\d{2}:\d{2}:\d{2})(?:[.,](\d{1,9}))?")
+        .unwrap_or_else(|e| panic!("Failed to create 'FORGE_DATE_TIME_REGEX': {}", e))
+});
+
+// JUL's SimpleFormatter: Jan 12, 2024 12:34:56 PM org.bukkit.Foo bar
+static JUL_DATE_TIME_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^[A-Z][a-z]{2} \d{1,2}, \d{4} \d{1,2}:\d{2}:\d{2} [AP]M")
+        .unwrap_or_else(|e| panic!("Failed to create 'JUL_DATE_TIME_REGEX': {}", e))
+});
+
+// Vanilla/Spigot: [12:34:56], [12:34:56 INFO], BungeeCord: 12:34:56 [INFO]
+static TIME_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^\[?(\d{2}:\d{2}:\d{2})(?:[.,](\d{1,9}))?")
+        .unwrap_or_else(|e| panic!("Failed to create 'TIME_REGEX': {}", e))
+});
+
+#[derive(Serialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub enum Timestamp {
+    DateTime(NaiveDateTime),
+    /// Only the time of day is known, see [`DateTracker`]
+    Time(NaiveTime),
+}
+
+impl Timestamp {
+    /// Parses the timestamp at the start of a line, skipping container prefixes.
+    ///
+    /// A timestamp with a date written by the server wins over the container's, which is
+    /// still better than a time of day alone.
+    pub fn parse(line: &str) -> Option<Self> {
+        let mut rest = line;
+        let mut container_timestamp = None;
+
+        if let Some(captures) = CONTAINER_TIMESTAMP_REGEX.captures(rest) {
+            container_timestamp = captures
+                .get(1)
+                .and_then(|m| DateTime::parse_from_rfc3339(m.as_str()).ok())
+                .map(|date_time| Timestamp::DateTime(date_time.naive_utc()));
+            rest = &rest[captures.get(0)?.end()..];
+        }
+
+        if let Some(m) = CONTAINER_NAME_REGEX.find(rest) {
+            rest = &rest[m.end()..];
+        }
+
+        let server_timestamp = Self::parse_server(rest);
+
+        match (server_timestamp, container_timestamp) {
+            (Some(Timestamp::DateTime(date_time)), _) => Some(Timestamp::DateTime(date_time)),
+            (_, Some(container)) => Some(container),
+            (server, None) => server,
+        }
+    }
+
+    fn parse_server(line: &str) -> Option<Self> {
+        if let Some(captures) = DATE_TIME_REGEX.captures(line) {
+            let date = NaiveDate::parse_from_str(captures.get(1)?.as_str(), "%Y-%m-%d").ok()?;
+            let time = time(&captures, 2)?;
+            return Some(Timestamp::DateTime(date.and_time(time)));
+        }
+
+        if let Some(captures) = FORGE_DATE_TIME_REGEX.captures(line) {
+            let date = NaiveDate::parse_from_str(captures.get(1)?.as_str(), "%d%b%Y").ok()?;
+            let time = time(&captures, 2)?;
+            return Some(Timestamp::DateTime(date.and_time(time)));
+        }
+
+        if let Some(m) = JUL_DATE_TIME_REGEX.find(line) {
+            let date_time =
+                NaiveDateTime::parse_from_str(m.as_str(), "%b %d, %Y %I:%M:%S %p").ok()?;
+            return Some(Timestamp::DateTime(date_time));
+        }
+
+        if let Some(captures) = TIME_REGEX.captures(line) {
+            return Some(Timestamp::Time(time(&captures, 1)?));
+        }
+
+        None
+    }
+
+    pub fn time(&self) -> NaiveTime {
+        match self {
+            Timestamp::DateTime(date_time) => date_time.time(),
+            Timestamp::Time(time) => *time,
+        }
+    }
+
+    pub fn date_time(&self) -> Option<NaiveDateTime> {
+        match self {
+            Timestamp::DateTime(date_time) => Some(*date_time),
+            Timestamp::Time(_) => None,
+        }
+    }
+}
+
+/// `HH:MM:SS` in group `idx`, fraction of a second in the next one.
+fn time(captures: &Captures, idx: usize) -> Option<NaiveTime> {
+    let time = NaiveTime::parse_from_str(captures.get(idx)?.as_str(), "%H:%M:%S").ok()?;
+
+    let nanos = match captures.get(idx + 1) {
+        Some(fraction) => {
+            let digits = fraction.as_str();
+            let nanos = digits.parse::<i64>().ok()?;
+            nanos * 10_i64.pow(9 - digits.len() as u32)
+        }
+        None => 0,
+    };
+
+    Some(time.overflowing_add_signed(TimeDelta::nanoseconds(nanos)).0)
+}
+
+/// Completes time-only timestamps with a date, e.g. one taken from a rotated log's file name
+/// (`2024-01-12-1.log.gz`), moving to the next day when the time wraps around midnight.
+#[derive(Clone, Debug)]
+pub(crate) struct DateTracker {
+    date: NaiveDate,
+    last_time: Option<NaiveTime>,
+}
+
+impl DateTracker {
+    pub(crate) fn new(date: NaiveDate) -> Self {
+        Self {
+            date,
+            last_time: None,
+        }
+    }
+
+    pub(crate) fn complete(&mut self, timestamp: Timestamp) -> Timestamp {
+        match timestamp {
+            Timestamp::DateTime(date_time) => {
+                self.date = date_time.date();
+                self.last_time = Some(date_time.time());
+                timestamp
+            }
+            Timestamp::Time(time) => {
+                // Clocks going back (DST, NTP) shouldn't count as a new day
+                if let Some(last_time) = self.last_time {
+                    if time < last_time && last_time - time > TimeDelta::hours(12) {
+                        self.date = self.date.succ_opt().unwrap_or(self.date);
+                    }
+                }
+
+                self.last_time = Some(time);
+                Timestamp::DateTime(self.date.and_time(time))
+            }
+        }
+    }
+}
@@ -0,0 +1,181 @@
+use mclog::parser::crash_report::{CrashMod, CrashReport};
+
+fn parse(fixture: &str) -> CrashReport {
+    let lines: Vec<String> = fixture.lines().map(String::from).collect();
+    CrashReport::find(&lines).unwrap()
+}
+
+fn ids(mods: &[CrashMod]) -> Vec<&str> {
+    mods.iter().map(|m| m.id.as_str()).collect()
+}
+
+#[test]
+fn vanilla() {
+    let report = parse(include_str!("fixtures/crash_reports/vanilla.txt"));
+
+    assert_eq!(report.start_line, 1);
+    assert_eq!(report.time.as_deref(), Some("2024-02-18 16:42:09"));
+    assert_eq!(report.description.as_deref(), Some("Ticking entity"));
+
+    let exception = report.exception.as_ref().unwrap();
+    assert_eq!(exception.class, "java.lang.IllegalArgumentException");
+    assert_eq!(exception.frames.len(), 13);
+    assert_eq!(
+        exception.frames[1].class,
+        "net.minecraft.world.entity.animal.Bee$BeeGrowCropGoal"
+    );
+    assert_eq!(exception.frames[1].method, "tick");
+    assert_eq!(exception.frames[1].line, Some(1143));
+
+    let titles: Vec<&str> = report.sections.iter().map(|s| s.title.as_str()).collect();
+    assert_eq!(
+        titles,
+        [
+            "Head",
+            "Entity being ticked",
+            "Affected level",
+            "System Details"
+        ]
+    );
+    assert_eq!(
+        report.section("Head").unwrap().detail("Thread"),
+        Some("Server thread")
+    );
+
+    let entity = report.section("Entity being ticked").unwrap();
+    assert_eq!(
+        entity.detail("Entity Type"),
+        Some("minecraft:bee (net.minecraft.world.entity.animal.Bee)")
+    );
+    assert_eq!(entity.frames.len(), 2);
+
+    assert_eq!(report.minecraft_version(), Some("1.20.4"));
+    assert_eq!(report.java_version(), Some("21.0.2"));
+    assert_eq!(report.brand(), None);
+    assert!(report.suspected_mods.is_empty());
+    assert!(report.mods.is_empty());
+}
+
+#[test]
+fn forge_1_12() {
+    let report = parse(include_str!("fixtures/crash_reports/forge_1_12.txt"));
+
+    assert_eq!(report.time.as_deref(), Some("3/14/24 9:26 PM"));
+    assert_eq!(
+        report.description.as_deref(),
+        Some("Exception in server tick loop")
+    );
+    let exception = report.exception.as_ref().unwrap();
+    assert_eq!(exception.class, "java.lang.NullPointerException");
+    assert_eq!(exception.frames[0].class, "com.example.pipes.tile.TilePipe");
+    assert_eq!(exception.frames.len(), 8);
+
+    assert_eq!(report.minecraft_version(), Some("1.12.2"));
+    assert_eq!(report.java_version(), Some("1.8.0_402"));
+    assert_eq!(report.brand(), Some("fml,forge"));
+    assert_eq!(
+        report.system_detail("Profiler Position"),
+        Some("N/A (disabled)")
+    );
+
+    assert_eq!(
+        ids(&report.mods),
+        ["minecraft", "mcp", "FML", "forge", "pipes"]
+    );
+    assert_eq!(
+        report.mods[4],
+        CrashMod {
+            id: "pipes".to_string(),
+            name: None,
+            version: Some("1.4.2".to_string()),
+            file: Some("pipes-1.12.2-1.4.2.jar".to_string()),
+        }
+    );
+}
+
+#[test]
+fn forge_1_20() {
+    let report = parse(include_str!("fixtures/crash_reports/forge_1_20.txt"));
+
+    assert_eq!(report.description.as_deref(), Some("Ticking block entity"));
+    let exception = report.exception.as_ref().unwrap();
+    assert_eq!(exception.class, "java.lang.ClassCastException");
+    assert_eq!(exception.frames.len(), 8);
+    assert_eq!(exception.frames[0].method, "lambda$getTicker$0");
+    assert_eq!(exception.frames[0].line, Some(87));
+
+    let block_entity = report.section("Block entity being ticked").unwrap();
+    assert_eq!(
+        block_entity.detail("Name"),
+        Some("machines:crusher // com.example.machines.block.entity.CrusherBlockEntity")
+    );
+    assert_eq!(block_entity.frames.len(), 2);
+
+    assert_eq!(report.minecraft_version(), Some("1.20.1"));
+    assert_eq!(report.java_version(), Some("17.0.10"));
+    assert_eq!(report.brand(), Some("forge"));
+    assert_eq!(
+        report.system_detail("Forge"),
+        Some("net.minecraftforge:47.2.20")
+    );
+
+    assert_eq!(
+        report.suspected_mods,
+        [CrashMod {
+            id: "machines".to_string(),
+            name: Some("Machines".to_string()),
+            version: Some("2.3.0".to_string()),
+            file: None,
+        }]
+    );
+    assert_eq!(ids(&report.mods), ["minecraft", "forge", "machines"]);
+    assert_eq!(
+        report.mods[2],
+        CrashMod {
+            id: "machines".to_string(),
+            name: Some("Machines".to_string()),
+            version: Some("2.3.0".to_string()),
+            file: Some("machines-1.20.1-2.3.0.jar".to_string()),
+        }
+    );
+}
+
+#[test]
+fn fabric() {
+    let report = parse(include_str!("fixtures/crash_reports/fabric.txt"));
+
+    assert_eq!(report.time.as_deref(), Some("2024-04-21 11:17:30"));
+    let exception = report.exception.as_ref().unwrap();
+    assert_eq!(exception.class, "java.lang.NoSuchMethodError");
+    assert_eq!(
+        exception.frames[0].class,
+        "com.example.sparkles.SparkleTicker"
+    );
+
+    assert_eq!(report.minecraft_version(), Some("1.20.4"));
+    assert_eq!(report.java_version(), Some("21.0.2"));
+    assert_eq!(report.brand(), Some("fabric"));
+    assert!(report.suspected_mods.is_empty());
+
+    // Jars nested in Fabric API aren't mods of their own
+    assert_eq!(
+        ids(&report.mods),
+        [
+            "fabric-api",
+            "fabricloader",
+            "java",
+            "lithium",
+            "minecraft",
+            "sparkles"
+        ]
+    );
+    assert_eq!(
+        report.mods[0],
+        CrashMod {
+            id: "fabric-api".to_string(),
+            name: Some("Fabric API".to_string()),
+            version: Some("0.97.0+1.20.4".to_string()),
+            file: None,
+        }
+    );
+}
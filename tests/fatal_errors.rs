@@ -0,0 +1,111 @@
+use mclog::parser::fatal_error::{FatalErrorCause, FatalErrorLog, NativeFrame};
+
+const GIB: u64 = 1024 * 1024 * 1024;
+
+fn parse(fixture: &str) -> FatalErrorLog {
+    let lines: Vec<String> = fixture.lines().map(String::from).collect();
+    FatalErrorLog::find(&lines).unwrap()
+}
+
+fn frame(kind: char, library: Option<&str>, symbol: &str) -> NativeFrame {
+    NativeFrame {
+        kind,
+        library: library.map(String::from),
+        symbol: symbol.to_string(),
+    }
+}
+
+#[test]
+fn sigsegv() {
+    let log = parse(include_str!("fixtures/fatal_errors/sigsegv.log"));
+
+    assert_eq!(log.signal.as_deref(), Some("SIGSEGV (0xb)"));
+    assert_eq!(log.pc.as_deref(), Some("0x00007f3a1c0b2e41"));
+    assert_eq!(log.pid, Some(48213));
+    assert_eq!(log.out_of_memory, None);
+    assert_eq!(log.java_version(), Some("21.0.2"));
+    assert_eq!(log.current_thread.as_deref(), Some("Server thread"));
+    assert_eq!(log.heap.max, Some(8 * GIB));
+    assert_eq!(log.heap.initial, Some(8 * GIB));
+    assert_eq!(log.physical_memory, Some(65768432 * 1024));
+    assert_eq!(log.container_memory_limit, None);
+
+    assert_eq!(
+        log.problematic_frame,
+        Some(frame(
+            'C',
+            Some("libc.so.6"),
+            "__memmove_avx_unaligned_erms+0x81"
+        ))
+    );
+    assert_eq!(log.native_frames.len(), 7);
+    assert_eq!(
+        log.native_frames[1],
+        frame(
+            'C',
+            Some("libzstd-jni-1.5.5-11213457890123456789.so"),
+            "ZSTD_decompressStream+0x311"
+        )
+    );
+    assert_eq!(
+        log.native_frames[5],
+        frame(
+            'J',
+            None,
+            "com.github.luben.zstd.ZstdInputStreamNoFinalizer.read([BII)I"
+        )
+    );
+    assert_eq!(log.java_frames.len(), 6);
+    assert_eq!(log.java_frames[4].kind, 'j');
+    assert_eq!(
+        log.java_frames[4].symbol,
+        "net.coreprotect.database.Database.readBlock(Ljava/io/InputStream;)[B+37"
+    );
+
+    // Crashed in libc, called from the zstd library bundled by a plugin
+    assert_eq!(log.cause, Some(FatalErrorCause::NativeLibrary));
+}
+
+#[test]
+fn out_of_memory() {
+    let log = parse(include_str!("fixtures/fatal_errors/out_of_memory.log"));
+
+    assert_eq!(log.signal, None);
+    assert_eq!(log.pid, None);
+    assert_eq!(
+        log.out_of_memory.as_deref(),
+        Some("Native memory allocation (mmap) failed to map 8589934592 bytes for G1 virtual space")
+    );
+    assert_eq!(log.jre_version.as_deref(), Some("(17.0.10+7) (build )"));
+    assert_eq!(log.java_version(), None);
+    assert_eq!(log.current_thread.as_deref(), Some("Unknown thread"));
+    assert_eq!(log.heap.max, Some(8 * GIB));
+    assert_eq!(log.heap.initial, Some(8 * GIB));
+    assert_eq!(log.physical_memory, Some(8126928 * 1024));
+    assert_eq!(log.container_memory_limit, None);
+    assert_eq!(log.problematic_frame, None);
+    assert_eq!(log.native_frames.len(), 7);
+    assert_eq!(log.native_frames[5].library.as_deref(), Some("libjli.so"));
+    assert!(log.java_frames.is_empty());
+
+    assert_eq!(log.cause, Some(FatalErrorCause::OutOfNativeMemory));
+}
+
+#[test]
+fn container() {
+    let log = parse(include_str!("fixtures/fatal_errors/container.log"));
+
+    assert_eq!(
+        log.out_of_memory.as_deref(),
+        Some("Native memory allocation (malloc) failed to allocate 1048576 bytes for AllocateHeap")
+    );
+    assert_eq!(log.java_version(), Some("21.0.3"));
+    assert_eq!(log.current_thread.as_deref(), Some("C2 CompilerThread0"));
+    assert_eq!(log.heap.max, Some(6 * GIB));
+    assert_eq!(log.heap.initial, Some(6 * GIB));
+    assert_eq!(log.container_memory_limit, Some(6815744 * 1024));
+    assert_eq!(log.physical_memory, Some(6815744 * 1024));
+    assert_eq!(log.native_frames.len(), 4);
+
+    assert_eq!(log.cause, Some(FatalErrorCause::ContainerOutOfMemory));
+}
@@ -0,0 +1,120 @@
+use chrono::NaiveDate;
+use mclog::{
+    analyzer::{Analyzer, Platform},
+    parser::{
+        log_level::EntryLevel,
+        parser::{LogEntry, Parser},
+        timestamp::Timestamp,
+    },
+};
+
+fn lines(fixture: &str) -> Vec<String> {
+    fixture.lines().map(String::from).collect()
+}
+
+fn entries(fixture: &str) -> Vec<LogEntry> {
+    Parser::new(lines(fixture), Vec::new()).unwrap().entries()
+}
+
+/// Every fixture is the same Paper startup, a plugin failing to enable and a restart.
+fn assert_entries(entries: &[LogEntry]) {
+    assert_eq!(entries.len(), 5);
+
+    let starting = &entries[0];
+    assert_eq!(starting.log_level, EntryLevel::Info);
+    assert_eq!(starting.thread.as_deref(), Some("Server thread"));
+    assert_eq!(
+        starting.logger.as_deref(),
+        Some("net.minecraft.server.MinecraftServer")
+    );
+    assert_eq!(
+        starting.timestamp,
+        Some(Timestamp::DateTime(
+            NaiveDate::from_ymd_opt(2024, 1, 12)
+                .unwrap()
+                .and_hms_milli_opt(12, 34, 56, 118)
+                .unwrap()
+        ))
+    );
+    assert!(starting
+        .message
+        .ends_with("Starting minecraft server version 1.20.4"));
+
+    let error = &entries[2];
+    assert_eq!(error.log_level, EntryLevel::Error);
+    assert_eq!(error.level_token.as_deref(), Some("ERROR"));
+    assert_eq!(error.logger.as_deref(), Some("EssentialsSpawn"));
+
+    let stack_trace = error.stack_trace.as_ref().unwrap();
+    assert_eq!(stack_trace.class, "java.lang.IllegalStateException");
+    assert_eq!(
+        stack_trace.message.as_deref(),
+        Some("Essentials is not loaded")
+    );
+    assert_eq!(stack_trace.frames.len(), 2);
+    assert_eq!(
+        stack_trace.frames[0].class,
+        "com.earth2me.essentials.spawn.EssentialsSpawn"
+    );
+    assert_eq!(stack_trace.frames[0].line, Some(31));
+
+    assert!(entries[3].message.ends_with("Stopping server"));
+}
+
+#[test]
+fn json_layout() {
+    let fixture = include_str!("fixtures/layouts/json.json");
+    let entries = entries(fixture);
+
+    assert_entries(&entries);
+    assert_eq!((entries[0].start_line, entries[0].end_line), (2, 15));
+    assert_eq!((entries[2].start_line, entries[2].end_line), (30, 66));
+    assert_eq!(
+        entries[2].stack_trace.as_ref().unwrap().frames[0]
+            .jar
+            .as_deref(),
+        Some("EssentialsSpawn-2.20.1.jar:?")
+    );
+}
+
+#[test]
+fn ecs_layout() {
+    let fixture = include_str!("fixtures/layouts/ecs.json");
+    let entries = entries(fixture);
+
+    assert_entries(&entries);
+    assert_eq!((entries[2].start_line, entries[2].end_line), (3, 3));
+}
+
+#[test]
+fn xml_layout() {
+    let fixture = include_str!("fixtures/layouts/xml.xml");
+    let entries = entries(fixture);
+
+    assert_entries(&entries);
+    assert_eq!((entries[0].start_line, entries[0].end_line), (4, 7));
+    assert_eq!((entries[2].start_line, entries[2].end_line), (14, 23));
+}
+
+#[test]
+fn structured_logs_are_analyzed_like_text() {
+    for fixture in [
+        include_str!("fixtures/layouts/json.json"),
+        include_str!("fixtures/layouts/ecs.json"),
+        include_str!("fixtures/layouts/xml.xml"),
+    ] {
+        let analyzer = Analyzer::new(&lines(fixture)).unwrap();
+
+        assert!(matches!(analyzer.platform, Platform::Paper));
+        assert_eq!(analyzer.session_ranges.len(), 2);
+    }
+}
+
+#[test]
+fn empty_structured_log() {
+    assert!(matches!(
+        Analyzer::new(&lines("[\n]")),
+        Err(mclog::Error::EmptyLog)
+    ));
+    assert!(matches!(Analyzer::new(&[]), Err(mclog::Error::EmptyLog)));
+}
@@ -0,0 +1,109 @@
+use mclog::{
+    analyzer::culprit::{self, SuspectKind},
+    parser::{
+        parser::{LogEntry, Parser},
+        thread_dump::ThreadDump,
+    },
+};
+use std::collections::HashMap;
+
+fn entries(fixture: &str) -> Vec<LogEntry> {
+    let lines = fixture.lines().map(String::from).collect();
+    Parser::new(lines, Vec::new()).unwrap().entries()
+}
+
+fn plugins(names: &[&str]) -> HashMap<String, String> {
+    names
+        .iter()
+        .map(|name| (name.to_string(), "1.0".to_string()))
+        .collect()
+}
+
+#[test]
+fn early_warning() {
+    let dumps = ThreadDump::find_all(&entries(include_str!(
+        "fixtures/thread_dumps/early_warning.log"
+    )));
+    assert_eq!(dumps.len(), 1);
+
+    let dump = &dumps[0];
+    assert!(dump.early_warning);
+    assert_eq!(
+        dump.version.as_deref(),
+        Some("1.20.4-435-e8f5ce8 (MC: 1.20.4)")
+    );
+    assert_eq!(dump.start_line, 8);
+    assert_eq!(dump.server_threads.len(), 1);
+    assert!(dump.threads.is_empty());
+
+    let server_thread = dump.server_thread().unwrap();
+    assert_eq!(server_thread.name, "Server thread");
+    assert_eq!(server_thread.id, Some(31));
+    assert_eq!(server_thread.state.as_deref(), Some("RUNNABLE"));
+    assert!(server_thread.native);
+    assert!(!server_thread.suspended);
+    assert!(server_thread.locked_monitors.is_empty());
+    assert_eq!(server_thread.frames.len(), 17);
+    assert_eq!(server_thread.stack_line, 15);
+    assert_eq!(server_thread.frames[0].class, "sun.nio.ch.SocketDispatcher");
+    assert_eq!(server_thread.frames[0].method, "read0");
+    assert_eq!(
+        server_thread.frames[7].class,
+        "com.example.shopkeeper.storage.SqlStorage"
+    );
+    assert_eq!(server_thread.frames[7].line, Some(118));
+
+    let suspect = culprit::hang_suspect(dump, &plugins(&["LuckPerms", "ShopKeeper"])).unwrap();
+    assert_eq!(suspect.name, "ShopKeeper");
+    assert_eq!(suspect.kind, SuspectKind::Plugin);
+}
+
+#[test]
+fn full_dump() {
+    let dumps = ThreadDump::find_all(&entries(include_str!(
+        "fixtures/thread_dumps/full_dump.log"
+    )));
+    assert_eq!(dumps.len(), 1);
+
+    let dump = &dumps[0];
+    assert!(!dump.early_warning);
+    assert_eq!(dump.version.as_deref(), Some("git-Paper-196 (MC: 1.20.1)"));
+    assert_eq!(dump.start_line, 7);
+    assert_eq!(dump.server_threads.len(), 1);
+
+    let names: Vec<&str> = dump.threads.iter().map(|t| t.name.as_str()).collect();
+    assert_eq!(
+        names,
+        [
+            "Reference Handler",
+            "Server thread",
+            "RegionRestore Worker #1"
+        ]
+    );
+
+    let server_thread = dump.server_thread().unwrap();
+    assert_eq!(server_thread.id, Some(28));
+    assert_eq!(server_thread.state.as_deref(), Some("BLOCKED"));
+    assert!(!server_thread.native);
+    assert_eq!(
+        server_thread.locked_monitors,
+        ["com.example.regionrestore.RestoreQueue.poll(RestoreQueue.java:52)"]
+    );
+    assert_eq!(server_thread.frames.len(), 11);
+    assert_eq!(server_thread.stack_line, 23);
+    assert_eq!(
+        server_thread.frames[0].class,
+        "com.example.regionrestore.RestoreQueue"
+    );
+    assert_eq!(server_thread.frames[0].line, Some(55));
+
+    let worker = &dump.threads[2];
+    assert_eq!(worker.id, Some(97));
+    assert_eq!(worker.state.as_deref(), Some("WAITING"));
+    assert_eq!(worker.frames.len(), 3);
+    assert_eq!(worker.stack_line, 56);
+
+    let suspect = culprit::hang_suspect(dump, &plugins(&["WorldGuard", "RegionRestore"])).unwrap();
+    assert_eq!(suspect.name, "RegionRestore");
+    assert_eq!(suspect.kind, SuspectKind::Plugin);
+}